@@ -0,0 +1,12 @@
+-- Add migration script here
+create table if not exists public.archive_run
+(
+    id          uuid default uuid_generate_v4() not null
+        primary key,
+    -- Start of the archived window, NULL when everything before the cutoff date is archived
+    start_date  date,
+    cutoff_date date                            not null,
+    stage       varchar(50)                     not null,
+    started_at  timestamp with time zone        not null,
+    finished_at timestamp with time zone
+);
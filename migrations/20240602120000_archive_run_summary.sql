@@ -0,0 +1,29 @@
+-- Add migration script here
+create table if not exists public.archive_run_summary
+(
+    id             uuid default uuid_generate_v4() not null
+        primary key,
+    archive_run_id uuid
+        references public.archive_run,
+    command        varchar(50)                     not null,
+    outcome        varchar(50)                     not null,
+    error          text,
+    started_at     timestamp with time zone        not null,
+    finished_at    timestamp with time zone
+);
+
+create table if not exists public.archive_run_provider_summary
+(
+    run_summary_id               uuid         not null
+        references public.archive_run_summary,
+    provider                     varchar(255) not null,
+    bets_archived                bigint       not null,
+    details_fetched              bigint       not null,
+    details_failed               bigint       not null,
+    details_skipped              bigint       not null,
+    circuit_opened               bigint       not null,
+    debts_upserted               bigint       not null,
+    opening_balance_rows_updated bigint       not null,
+    duration_ms                  bigint       not null,
+    primary key (run_summary_id, provider)
+);
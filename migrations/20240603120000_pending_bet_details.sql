@@ -0,0 +1,23 @@
+-- Add migration script here
+create table if not exists public.pending_bet_details
+(
+    bet_id                     uuid                     not null
+        primary key,
+    provider                   varchar(255)             not null,
+    provider_bet_id            varchar(255)             not null,
+    user_id                    uuid                     not null,
+    username                   varchar(255)             not null,
+    currency                   varchar(10)              not null,
+    details                    text,
+    transactions               text[]                   not null,
+    provider_game_vendor_id    varchar(255)             not null,
+    attempts                   integer default 0        not null,
+    next_attempt_at            timestamp with time zone not null,
+    last_error                 text,
+    -- Set when the entry can never be retried, e.g. its provider no longer exists
+    dead_at                    timestamp with time zone,
+    created_at                 timestamp with time zone not null
+);
+
+create index if not exists "IDX_next_attempt_at_pending_bet_details"
+    on public.pending_bet_details (next_attempt_at);
@@ -32,6 +32,8 @@ pub fn create_credit_debt_models(
                 id: Uuid::new_v4(),
                 username: state
                     .username_by_user_id
+                    .lock()
+                    .unwrap()
                     .get(&user_id)
                     .ok_or_else(|| anyhow!("username was not found for user_id: {}", user_id))?
                     .clone(),
@@ -51,16 +53,17 @@ pub fn create_credit_debt_models(
 pub fn calculate_debt_by_bet(
     bet: &Bet,
     existing_figures: &mut FxHashMap<UserID, CurrencyAmount>,
-    state: &mut State,
+    state: &State,
 ) -> Result<()> {
-    let bet_user_upline = state
-        .upline
+    let upline = state.upline.lock().unwrap();
+    let mut username_by_user_id = state.username_by_user_id.lock().unwrap();
+
+    let bet_user_upline = upline
         .get(&bet.user_id)
         .with_context(|| format!("Not found upline for user: {}", &bet.user_id))?;
 
     for user in bet_user_upline {
-        state
-            .username_by_user_id
+        username_by_user_id
             .entry(user.id)
             .or_insert(user.username.clone());
 
@@ -1,110 +1,112 @@
+use anyhow::Result;
+use log::error;
 use serde_json::json;
+use sqlx::PgPool;
 
 use crate::{
-    enums::provider::{GameProvider, LiveCasinoProvider, OnlineCasinoProvider, SlotProvider},
-    helpers::State,
+    connectors::{
+        circuit_breaker::{CircuitBreaker, Transition},
+        ConnectorDisabled,
+    },
+    enums::provider::GameProvider,
+    helpers::{
+        logger::{log_warning, log_warning_with_payload},
+        metrics, State,
+    },
+    types::BetID,
 };
 
-use super::loader::{Bet, BetDetails};
+use super::loader::{BetDetails, BetDetailsRequest};
 
-pub async fn extend_bet_with_details(
-    state: &mut State,
-    bet: &Bet,
+/// Bet is archived anyway, so the failure is only logged with everything needed to investigate it.
+/// Logging is best-effort as well: a failed insert into 'system_log' must not stop the chunk.
+pub async fn log_details_failure(
+    pg: &PgPool,
+    bet_id: BetID,
     provider: GameProvider,
-) -> Option<BetDetails> {
-    match provider {
-        GameProvider::LiveCasino(LiveCasinoProvider::Sexy) => state
-            .connectors
-            .ae
-            .get_transaction_history_result(&bet.username, &bet.provider_bet_id)
-            .await
-            .ok()
-            .map(|url| BetDetails {
-                id: bet.id.clone(),
-                details: None,
-                replay: Some(url),
-            }),
+    err: anyhow::Error,
+) {
+    let logged = log_warning_with_payload(
+        pg,
+        format!("Failed to fetch details of bet '{bet_id}' from '{provider}'"),
+        json!({
+            "bet_id": bet_id,
+            "provider": provider.to_string(),
+            "error": err.chain().map(|cause| cause.to_string()).collect::<Vec<String>>(),
+        }),
+    )
+    .await;
 
-        GameProvider::LiveCasino(LiveCasinoProvider::Pragmatic)
-        | GameProvider::Slot(SlotProvider::Pragmatic) => {
-            if bet.details.is_none() {
-                return state
-                    .connectors
-                    .pragmatic
-                    .get_bet_round_history(&bet)
-                    .await
-                    .ok()
-                    .map(|url| BetDetails {
-                        id: bet.id.clone(),
-                        details: Some(json!({ "result": url }).to_string()),
-                        replay: None,
-                    });
-            };
+    if let Err(e) = logged {
+        error!("{:?}", e);
+    }
+}
 
-            None
+/// Providers without a connector have nothing to add to their bets.
+/// Fails with `ConnectorDisabled` when the connector of the provider is not loaded,
+/// and with `CircuitOpen` without calling the provider while its circuit breaker is open.
+pub async fn extend_bet_with_details(
+    state: &State,
+    bet: &BetDetailsRequest,
+    provider: GameProvider,
+) -> Result<Option<BetDetails>> {
+    let Some(registered) = state.connectors.get(provider) else {
+        if state.connectors.is_disabled(provider) {
+            return Err(ConnectorDisabled(provider).into());
         }
 
-        GameProvider::Slot(SlotProvider::RoyalSlotGaming) => state
-            .connectors
-            .royal_slot_gaming
-            .get_game_round_history(&bet, None)
-            .await
-            .ok()
-            .map(|url| BetDetails {
-                id: bet.id.clone(),
-                details: Some(json!({ "result": url }).to_string()),
-                replay: None,
-            }),
+        return Ok(None);
+    };
+
+    let breaker = &registered.circuit_breaker;
 
-        GameProvider::Slot(SlotProvider::Ameba) => state
-            .connectors
-            .ameba
-            .get_round_history(&bet.username, &bet.provider_bet_id)
-            .await
-            .ok()
-            .map(|url| BetDetails {
-                id: bet.id.clone(),
-                details: Some(json!({ "result": url }).to_string()),
-                replay: None,
-            }),
+    let permit = match breaker.acquire() {
+        Ok(permit) => permit,
+        Err(e) => {
+            state
+                .stats
+                .lock()
+                .unwrap()
+                .provider(provider)
+                .details_skipped += 1;
+            return Err(e.into());
+        }
+    };
+
+    let acquired = permit.transition();
+    let result =
+        metrics::observe_connector(breaker.name(), registered.connector.fetch_details(bet)).await;
+    let recorded = permit.record(result.is_ok());
 
-        GameProvider::OnlineCasino(OnlineCasinoProvider::Arcadia) => state
-            .connectors
-            .arcadia
-            .get_bet_history(&bet.provider_bet_id)
-            .await
-            .ok()
-            .map(|url| BetDetails {
-                id: bet.id.clone(),
-                details: Some(json!({ "result": url }).to_string()),
-                replay: None,
-            }),
+    // State is settled first, so a failed log can't leave the probe unrecorded
+    for transition in [acquired, recorded].into_iter().flatten() {
+        report_transition(state, breaker, provider, transition).await?;
+    }
 
-        GameProvider::OnlineCasino(OnlineCasinoProvider::Kingmaker) => state
-            .connectors
-            .king_maker
-            .get_round_history(&bet.username, &bet.provider_bet_id)
-            .await
-            .ok()
-            .map(|url| BetDetails {
-                id: bet.id.clone(),
-                details: Some(json!({ "result": url }).to_string()),
-                replay: None,
-            }),
+    result
+}
 
-        GameProvider::Slot(SlotProvider::Relax)
-        | GameProvider::Slot(SlotProvider::YGG)
-        | GameProvider::Slot(SlotProvider::Hacksaw) => state
-            .connectors
-            .dot_connections
-            .get_bet_history(&bet)
-            .await
-            .ok()
-            .map(|url| BetDetails {
-                id: bet.id.clone(),
-                details: Some(json!({ "result": url }).to_string()),
-                replay: None,
-            }),
-        _ => None,
+async fn report_transition(
+    state: &State,
+    breaker: &CircuitBreaker,
+    provider: GameProvider,
+    transition: Transition,
+) -> Result<()> {
+    if transition == Transition::Opened {
+        state
+            .stats
+            .lock()
+            .unwrap()
+            .provider(provider)
+            .circuit_opened += 1;
     }
+
+    log_warning(
+        &state.pg,
+        format!(
+            "Circuit breaker of '{}' connector {transition} on '{provider}' bet details",
+            breaker.name()
+        ),
+    )
+    .await
 }
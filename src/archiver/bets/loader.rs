@@ -2,19 +2,30 @@ use std::str::FromStr;
 
 use anyhow::{Context, Result};
 use arrayvec::ArrayVec;
+use serde::Serialize;
+use serde_json::json;
 use smallvec::SmallVec;
 use sqlx::{
-    prelude::FromRow, Execute, MySql, MySqlPool, PgPool, Postgres, QueryBuilder, Transaction,
+    prelude::FromRow, Execute, Executor, MySql, MySqlPool, PgPool, Postgres, QueryBuilder,
+    Transaction,
 };
-use time::{Date, Duration, OffsetDateTime};
+use strum::VariantArray;
+use time::{Date, OffsetDateTime};
 use uuid::Uuid;
 
 use crate::{
-    archiver::CHUNK_SIZE,
-    consts::{BET_DETAIL_REPORT_TABLE_NAME, CREDIT_DEBT_TABLE_NAME, MARIA_DB_SCHEMA, SCHEMA},
-    enums::{bet::BetStatus, provider::GameProvider, PositionEnum},
+    archiver::{retention::StatusCutoff, CHUNK_SIZE},
+    consts::{
+        BET_DETAIL_REPORT_TABLE_NAME, CREDIT_DEBT_TABLE_NAME, LOTTERY_BET_TABLE_NAME,
+        MARIA_DB_SCHEMA, SCHEMA,
+    },
+    enums::{
+        bet::BetStatus,
+        provider::{GameProvider, Lottery},
+        PositionEnum,
+    },
     helpers::{
-        get_hong_kong_11_hours_from_date,
+        get_hong_kong_11_hours_from_date, metrics,
         query_helper::{get_archive_schema_name, get_bet_table_name, get_dynamic_table_name},
     },
     types::{
@@ -25,21 +36,33 @@ use crate::{
 
 use super::debts::DEBT_SIZE;
 
+// Every bet takes 47 placeholders, MariaDB allows 65535 per statement
+const MARIA_DB_BET_INSERT_BATCH_SIZE: usize = 500;
+
 pub async fn get_target_data_bench(
     pg_pool: &PgPool,
     table: &str,
+    lottery_kinds: Option<&[String]>,
     start_date: Option<Date>,
+    cutoffs: &[StatusCutoff],
+    limit: usize,
+    after: Option<(OffsetDateTime, BetID)>,
 ) -> Result<ChunkVec<Bet>> {
-    let yesterday =
-        get_hong_kong_11_hours_from_date(OffsetDateTime::now_utc().date() - Duration::days(1));
-
-    let mut where_query = vec!["last_status_change < $3"];
+    let _timer = metrics::db_query_timer("pg", "get_target_data_bench");
 
-    if start_date.is_some() {
-        where_query.push("last_status_change >= $4");
+    if cutoffs.is_empty() {
+        // Retention policy excludes every status
+        return Ok(ArrayVec::new());
     }
 
-    let raw_bets: Vec<RawBet> = sqlx::query_as(&format!(
+    // Only the shared lottery table knows which lottery a bet belongs to
+    let kind_column = if table == LOTTERY_BET_TABLE_NAME {
+        "kind"
+    } else {
+        "NULL::varchar AS kind"
+    };
+
+    let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new(format!(
         r#"
             SELECT
                 id,
@@ -63,25 +86,63 @@ pub async fn get_target_data_bench(
                 transactions,
                 provider_bet_id,
                 provider_game_vendor_id,
-                provider_game_vendor_label
+                provider_game_vendor_label,
+                {kind_column}
             FROM
                 public.{table}
-            WHERE
-                status NOT IN ($1, $2)
-            AND
-                ({})
-            ORDER BY last_status_change
-            LIMIT {CHUNK_SIZE}
-        "#,
-        where_query.join(" AND ")
-    ))
-    .bind(BetStatus::Active.to_string())
-    .bind(BetStatus::Pending.to_string())
-    .bind(yesterday)
-    .bind(start_date)
-    .fetch_all(pg_pool)
-    .await
-    .with_context(|| format!("Failed to fetch bet chunk from '{table}'"))?;
+            WHERE (
+        "#
+    ));
+
+    for (index, cutoff) in cutoffs.iter().enumerate() {
+        if index > 0 {
+            query_builder.push(" OR ");
+        }
+
+        query_builder
+            .push("(status = ANY(")
+            .push_bind(cutoff.statuses.clone())
+            .push(") AND last_status_change < ")
+            .push_bind(get_hong_kong_11_hours_from_date(cutoff.cutoff_date))
+            .push(")");
+    }
+
+    query_builder.push(")");
+
+    if let Some(lottery_kinds) = lottery_kinds {
+        query_builder
+            .push(" AND kind = ANY(")
+            .push_bind(lottery_kinds.to_vec())
+            .push(")");
+    }
+
+    if let Some(start_date) = start_date {
+        query_builder
+            .push(" AND last_status_change >= ")
+            .push_bind(get_hong_kong_11_hours_from_date(start_date));
+    }
+
+    // Bets which failed MariaDB verification stay in PG and dry run deletes nothing,
+    // so the next chunk has to start after the previous one
+    if let Some((last_status_change, id)) = after {
+        query_builder
+            .push(" AND (last_status_change, id) > (")
+            .push_bind(last_status_change)
+            .push(", ")
+            .push_bind(id)
+            .push(")");
+    }
+
+    query_builder.push(format!(
+        " ORDER BY last_status_change, id LIMIT {}",
+        limit.min(CHUNK_SIZE)
+    ));
+
+    let raw_bets: Vec<RawBet> = query_builder
+        .build_query_as()
+        .fetch_all(pg_pool)
+        .await
+        .with_context(|| format!("Failed to fetch bet chunk from '{table}'"))?;
 
     let mut bets = ArrayVec::new();
 
@@ -116,6 +177,7 @@ struct RawBet {
     provider_bet_id: ProviderBetID,
     provider_game_vendor_id: ProviderGameVendorID,
     provider_game_vendor_label: ProviderGameVendorLabel,
+    kind: Option<String>,
 }
 
 impl RawBet {
@@ -143,6 +205,8 @@ impl RawBet {
             provider_bet_id: self.provider_bet_id,
             provider_game_vendor_id: self.provider_game_vendor_id,
             provider_game_vendor_label: self.provider_game_vendor_label,
+            // Lottery bets are fetched by `Lottery` names, so every fetched kind has a variant
+            kind: self.kind.and_then(|kind| Lottery::from_str(&kind).ok()),
         };
 
         Ok(bet)
@@ -173,6 +237,38 @@ pub struct Bet {
     pub provider_bet_id: ProviderBetID,
     pub provider_game_vendor_id: ProviderGameVendorID,
     pub provider_game_vendor_label: ProviderGameVendorLabel,
+    /// Set only for bets from the shared lottery table
+    pub kind: Option<Lottery>,
+}
+
+/// Only the fields of a bet which connectors need to look up its details.
+/// Also all that is kept of a bet whose details are retried after it left PG.
+#[derive(Debug, Clone, FromRow)]
+pub struct BetDetailsRequest {
+    #[sqlx(rename = "bet_id")]
+    pub id: BetID,
+    pub user_id: UserID,
+    pub username: Username,
+    pub currency: Currency,
+    pub details: Option<String>,
+    pub transactions: Vec<String>,
+    pub provider_bet_id: ProviderBetID,
+    pub provider_game_vendor_id: ProviderGameVendorID,
+}
+
+impl From<&Bet> for BetDetailsRequest {
+    fn from(bet: &Bet) -> Self {
+        Self {
+            id: bet.id,
+            user_id: bet.user_id,
+            username: bet.username.clone(),
+            currency: bet.currency.clone(),
+            details: bet.details.clone(),
+            transactions: bet.transactions.clone(),
+            provider_bet_id: bet.provider_bet_id.clone(),
+            provider_game_vendor_id: bet.provider_game_vendor_id.clone(),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -182,6 +278,17 @@ pub struct BetDetails {
     pub replay: Option<Url>,
 }
 
+impl BetDetails {
+    /// Most providers return a link to their own bet history page, saved as `{"result": ...}`
+    pub fn with_result(id: BetID, result: impl Serialize) -> Self {
+        Self {
+            id,
+            details: Some(json!({ "result": result }).to_string()),
+            replay: None,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct User {
     pub id: UserID,
@@ -193,6 +300,8 @@ pub async fn get_upline(
     user_id: &UserID,
     transaction: &mut Transaction<'_, sqlx::Postgres>,
 ) -> Result<Vec<User>> {
+    let _timer = metrics::db_query_timer("pg", "get_upline");
+
     sqlx::query_as!(
         User,
         r#"
@@ -223,11 +332,27 @@ pub struct CreditDebt {
     pub debt_amount: i64,
 }
 
+/// Arbitrary key of the advisory lock guarding debt and opening balance writes
+const FIGURES_LOCK_KEY: i64 = 7_420_001;
+
+/// Lock is held until the transaction is committed or rolled back
+pub async fn lock_figures(transaction: &mut Transaction<'_, sqlx::Postgres>) -> Result<()> {
+    sqlx::query("SELECT pg_advisory_xact_lock($1)")
+        .bind(FIGURES_LOCK_KEY)
+        .execute(&mut **transaction)
+        .await
+        .context("Failed to acquire figures advisory lock")?;
+
+    Ok(())
+}
+
 pub async fn save_debts(
     pg_transaction: &mut Transaction<'_, Postgres>,
     debts: SmallVec<[CreditDebt; DEBT_SIZE]>,
     date: Date,
-) -> Result<()> {
+) -> Result<u64> {
+    let _timer = metrics::db_query_timer("pg", "save_debts");
+
     let db_schema = get_archive_schema_name(date);
     let table_name = get_dynamic_table_name(CREDIT_DEBT_TABLE_NAME, date);
 
@@ -264,7 +389,7 @@ pub async fn save_debts(
         query.sql()
     );
 
-    sqlx::query_with(
+    let result = sqlx::query_with(
         &sql,
         query
             .take_arguments()
@@ -274,7 +399,33 @@ pub async fn save_debts(
     .await
     .context("Failed to save debts")?;
 
-    Ok(())
+    Ok(result.rows_affected())
+}
+
+/// Lottery bets settled before the cutoff which kind is not a known `Lottery`.
+/// They are never fetched by lottery jobs and stay in PG.
+pub async fn count_unmatched_lottery_bets(
+    pg_pool: &PgPool,
+    start_date: Option<Date>,
+    cutoff_date: Date,
+) -> Result<i64> {
+    let known_kinds: Vec<String> = Lottery::VARIANTS.iter().map(|l| l.to_string()).collect();
+
+    sqlx::query_scalar(&format!(
+        r#"
+            SELECT COUNT(*)
+            FROM public.{LOTTERY_BET_TABLE_NAME}
+            WHERE (kind IS NULL OR NOT (kind = ANY($1)))
+                AND last_status_change < $2
+                AND ($3::timestamptz IS NULL OR last_status_change >= $3)
+        "#
+    ))
+    .bind(known_kinds)
+    .bind(get_hong_kong_11_hours_from_date(cutoff_date))
+    .bind(start_date.map(get_hong_kong_11_hours_from_date))
+    .fetch_one(pg_pool)
+    .await
+    .context("Failed to count lottery bets of unknown kind")
 }
 
 pub async fn delete_bets_by_ids(
@@ -282,6 +433,8 @@ pub async fn delete_bets_by_ids(
     provider: GameProvider,
     transaction: &mut Transaction<'_, sqlx::Postgres>,
 ) -> Result<()> {
+    let _timer = metrics::db_query_timer("pg", "delete_bets_by_ids");
+
     let table_name = get_bet_table_name(provider);
     let schema = &*SCHEMA;
 
@@ -315,6 +468,8 @@ pub async fn insert_bet_details_to_details_table(
     mysql: &MySqlPool,
     details: Vec<BetDetails>,
 ) -> Result<()> {
+    let _timer = metrics::db_query_timer("maria_db", "insert_bet_details_to_details_table");
+
     let schema = &*MARIA_DB_SCHEMA;
 
     let mut query_builder: QueryBuilder<MySql> = QueryBuilder::new(format!(
@@ -327,6 +482,10 @@ pub async fn insert_bet_details_to_details_table(
             .push_bind(r.replay);
     });
 
+    // Details of a chunk which was not committed in PG are inserted again on the next run
+    query_builder
+        .push(" ON DUPLICATE KEY UPDATE details = VALUES(details), replay = VALUES(replay)");
+
     let mut query = query_builder.build();
 
     sqlx::query_with(
@@ -342,7 +501,247 @@ pub async fn insert_bet_details_to_details_table(
     Ok(())
 }
 
+pub async fn insert_bets_to_maria_db(
+    mysql: &MySqlPool,
+    bets: &[Bet],
+    provider: GameProvider,
+) -> Result<()> {
+    let _timer = metrics::db_query_timer("maria_db", "insert_bets_to_maria_db");
+
+    let schema = &*MARIA_DB_SCHEMA;
+
+    let mut transaction = mysql
+        .begin()
+        .await
+        .context("Failed to start MariaDB transaction")?;
+
+    // Remove copies left by a previous attempt, e.g. of a run interrupted before its PG commit
+    let bet_ids: Vec<BetID> = bets.iter().map(|bet| bet.id).collect();
+    delete_maria_db_bets_by_ids(&mut *transaction, &bet_ids).await?;
+
+    for batch in bets.chunks(MARIA_DB_BET_INSERT_BATCH_SIZE) {
+        let mut query_builder: QueryBuilder<MySql> = QueryBuilder::new(format!(
+            r#"
+                INSERT INTO {schema}.bet (
+                    id,
+                    provider_bet_id,
+                    transaction_ids,
+                    provider_game_vendor_id,
+                    provider_game_vendor_label,
+                    creation_date,
+                    last_status_change,
+                    stake,
+                    valid_amount,
+                    wl,
+                    user_id,
+                    username,
+                    ip,
+                    status,
+                    currency,
+                    transactions,
+                    pt_by_position_0,
+                    pt_by_position_1,
+                    pt_by_position_2,
+                    pt_by_position_3,
+                    pt_by_position_4,
+                    pt_by_position_5,
+                    pt_by_position_6,
+                    commission_percent_0,
+                    commission_percent_1,
+                    commission_percent_2,
+                    commission_percent_3,
+                    commission_percent_4,
+                    commission_percent_5,
+                    commission_percent_6,
+                    commission_amount_0,
+                    commission_amount_1,
+                    commission_amount_2,
+                    commission_amount_3,
+                    commission_amount_4,
+                    commission_amount_5,
+                    commission_amount_6,
+                    funds_delta_0,
+                    funds_delta_1,
+                    funds_delta_2,
+                    funds_delta_3,
+                    funds_delta_4,
+                    funds_delta_5,
+                    funds_delta_6,
+                    details,
+                    replay,
+                    provider
+                )
+            "#
+        ));
+
+        let mut rows = vec![];
+
+        for bet in batch {
+            rows.push((
+                bet,
+                serde_json::to_string(&bet.transaction_ids).with_context(|| {
+                    format!("Failed to serialize transaction_ids of bet {}", bet.id)
+                })?,
+                serde_json::to_string(&bet.transactions).with_context(|| {
+                    format!("Failed to serialize transactions of bet {}", bet.id)
+                })?,
+            ));
+        }
+
+        query_builder.push_values(rows, |mut b, (bet, transaction_ids, transactions)| {
+            b.push_bind(bet.id.to_string())
+                .push_bind(bet.provider_bet_id.clone())
+                .push_bind(transaction_ids)
+                .push_bind(bet.provider_game_vendor_id.clone())
+                .push_bind(bet.provider_game_vendor_label.clone())
+                .push_bind(bet.creation_date)
+                .push_bind(bet.last_status_change)
+                .push_bind(bet.stake)
+                .push_bind(bet.valid_amount)
+                .push_bind(bet.wl)
+                .push_bind(bet.user_id.to_string())
+                .push_bind(bet.username.clone())
+                .push_bind(bet.ip.clone())
+                .push_bind(bet.status.to_string())
+                .push_bind(bet.currency.clone())
+                .push_bind(transactions);
+
+            for amounts in [
+                bet.pt_by_position,
+                bet.commission_percent,
+                bet.commission_amount,
+                bet.funds_delta,
+            ] {
+                for amount in amounts {
+                    b.push_bind(amount);
+                }
+            }
+
+            // Chunks are split by lottery kind, so the provider of a lottery bet is its kind
+            b.push_bind(bet.details.clone())
+                .push_bind(bet.replay.clone())
+                .push_bind(provider.to_string());
+        });
+
+        let mut query = query_builder.build();
+
+        sqlx::query_with(
+            query.sql(),
+            query
+                .take_arguments()
+                .context("Failed to take arguments for insert_bets_to_maria_db query")?,
+        )
+        .execute(&mut *transaction)
+        .await
+        .context("Failed to insert bets to MariaDB")?;
+    }
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit MariaDB bets transaction")
+}
+
+pub async fn delete_maria_db_bets_by_ids<'e, E>(executor: E, bet_ids: &[BetID]) -> Result<()>
+where
+    E: Executor<'e, Database = MySql>,
+{
+    let schema = &*MARIA_DB_SCHEMA;
+
+    let mut query_builder: QueryBuilder<MySql> =
+        QueryBuilder::new(format!("DELETE FROM {schema}.bet WHERE id IN ("));
+
+    let mut separated = query_builder.separated(",");
+
+    for id in bet_ids {
+        separated.push_bind(id.to_string());
+    }
+
+    separated.push_unseparated(")");
+
+    query_builder
+        .build()
+        .execute(executor)
+        .await
+        .context("Failed to delete bet copies from MariaDB")?;
+
+    Ok(())
+}
+
+#[derive(FromRow)]
+pub struct ArchivedBet {
+    pub id: String,
+    pub stake: i64,
+    pub wl: Option<i64>,
+    pub status: String,
+    pub funds_delta_0: i64,
+    pub funds_delta_1: i64,
+    pub funds_delta_2: i64,
+    pub funds_delta_3: i64,
+    pub funds_delta_4: i64,
+    pub funds_delta_5: i64,
+    pub funds_delta_6: i64,
+}
+
+impl ArchivedBet {
+    pub fn funds_delta(&self) -> AmountByPosition {
+        [
+            self.funds_delta_0,
+            self.funds_delta_1,
+            self.funds_delta_2,
+            self.funds_delta_3,
+            self.funds_delta_4,
+            self.funds_delta_5,
+            self.funds_delta_6,
+        ]
+    }
+}
+
+pub async fn get_maria_db_bets_by_ids(
+    mysql: &MySqlPool,
+    bet_ids: &[BetID],
+) -> Result<Vec<ArchivedBet>> {
+    let _timer = metrics::db_query_timer("maria_db", "get_maria_db_bets_by_ids");
+
+    let schema = &*MARIA_DB_SCHEMA;
+
+    let mut query_builder: QueryBuilder<MySql> = QueryBuilder::new(format!(
+        r#"
+            SELECT
+                id,
+                stake,
+                wl,
+                status,
+                funds_delta_0,
+                funds_delta_1,
+                funds_delta_2,
+                funds_delta_3,
+                funds_delta_4,
+                funds_delta_5,
+                funds_delta_6
+            FROM {schema}.bet
+            WHERE id IN (
+        "#
+    ));
+
+    let mut separated = query_builder.separated(",");
+
+    for id in bet_ids {
+        separated.push_bind(id.to_string());
+    }
+
+    separated.push_unseparated(")");
+
+    query_builder
+        .build_query_as()
+        .fetch_all(mysql)
+        .await
+        .context("Failed to fetch archived bets from MariaDB")
+}
+
 pub async fn update_bet_details(mysql: &MySqlPool) -> Result<()> {
+    let _timer = metrics::db_query_timer("maria_db", "update_bet_details");
+
     let schema = &*MARIA_DB_SCHEMA;
 
     sqlx::query(&format!(
@@ -1,15 +1,19 @@
+use std::time::Instant;
+
 use anyhow::Result;
 use arrayvec::ArrayVec;
+use futures::{stream, StreamExt};
 use rustc_hash::FxHashMap;
 use smallvec::SmallVec;
 use sqlx::Transaction;
 use time::{Date, OffsetDateTime};
 
 use crate::{
+    connectors::{circuit_breaker::CircuitOpen, ConnectorDisabled},
     consts::OPENING_BALANCE_TABLE_NAME,
     enums::provider::GameProvider,
     helpers::{
-        add_month, get_figures_date,
+        add_month, get_figures_date, metrics,
         query_helper::{get_archive_schema_name, get_dynamic_table_name},
         State,
     },
@@ -17,19 +21,29 @@ use crate::{
 };
 
 use self::{
-    debts::{calculate_debt_by_bet, create_credit_debt_models, DEBT_SIZE},
-    details::extend_bet_with_details,
+    debts::{calculate_debt_by_bet, create_credit_debt_models},
+    details::{extend_bet_with_details, log_details_failure},
     loader::{
-        delete_bets_by_ids, get_upline, insert_bet_details_to_details_table, save_debts, Bet,
+        delete_bets_by_ids, get_upline, insert_bet_details_to_details_table,
+        insert_bets_to_maria_db, lock_figures, save_debts, Bet, BetDetails, BetDetailsRequest,
         CreditDebt,
     },
+    pending_details::enqueue_pending_details,
+    verification::keep_verified_bets,
 };
 
-use super::{opening_balance::loader::update_opening_balance_amount, CHUNK_SIZE};
+use super::{
+    opening_balance::loader::{count_opening_balance_rows, update_opening_balance_amount},
+    CHUNK_SIZE,
+};
 
 mod debts;
-mod details;
+pub mod details;
 pub mod loader;
+pub mod pending_details;
+mod verification;
+
+pub(crate) use self::debts::DEBT_SIZE;
 
 #[derive(Debug)]
 struct CurrencyAmount {
@@ -43,26 +57,44 @@ type WlByDateByUser = FxHashMap<Date, FxHashMap<UserID, i64>>;
 pub async fn handle_bet_chunk(
     provider: GameProvider,
     bets: ChunkVec<Bet>,
-    state: &mut State,
+    state: &State,
     pg_transaction: &mut Transaction<'_, sqlx::Postgres>,
 ) -> Result<()> {
+    let started_at = Instant::now();
+
+    let bets = if state.options.dry_run {
+        bets
+    } else {
+        // Bets must be in MariaDB before they are deleted from PG in 'save_all'.
+        // Figures are calculated only for verified bets, the rest stay in PG.
+        insert_bets_to_maria_db(&state.maria_db, &bets, provider).await?;
+        keep_verified_bets(state, provider, bets).await?
+    };
+
+    if bets.is_empty() {
+        return Ok(());
+    }
+
     let mut bet_ids: ChunkVec<BetID> = ArrayVec::new();
     let mut debts: DebtsByDate = FxHashMap::default();
 
     let mut wl_by_date_by_user: WlByDateByUser = FxHashMap::default();
-    let mut bet_details = vec![];
 
-    for bet in bets {
+    for bet in &bets {
         bet_ids.push(bet.id);
 
         state
             .username_by_user_id
+            .lock()
+            .unwrap()
             .entry(bet.user_id)
             .or_insert(bet.username.clone());
 
-        if !state.upline.contains_key(&bet.user_id) {
+        let has_upline = state.upline.lock().unwrap().contains_key(&bet.user_id);
+
+        if !has_upline {
             let upline = get_upline(&bet.user_id, pg_transaction).await?;
-            state.upline.insert(bet.user_id, upline);
+            state.upline.lock().unwrap().insert(bet.user_id, upline);
         }
 
         let figures_date = get_figures_date(bet.last_status_change);
@@ -76,27 +108,97 @@ pub async fn handle_bet_chunk(
 
         if state.credit_players.contains_key(&bet.user_id) {
             let existing_debts = debts.entry(figures_date).or_insert_with(FxHashMap::default);
-            calculate_debt_by_bet(&bet, existing_debts, state)?;
+            calculate_debt_by_bet(bet, existing_debts, state)?;
         }
+    }
+
+    let credit_debts = create_credit_debt_models(debts, state)?;
 
-        if let Some(detail) = extend_bet_with_details(state, &bet, provider).await {
-            bet_details.push(detail);
+    if state.options.dry_run {
+        let opening_balance_rows =
+            count_opening_balance_rows_by_month(pg_transaction, &wl_by_date_by_user).await?;
+
+        state.dry_run_report.lock().unwrap().record(
+            provider,
+            bet_ids.len(),
+            &wl_by_date_by_user,
+            &credit_debts,
+            &opening_balance_rows,
+        );
+
+        return Ok(());
+    }
+
+    // Every detail carries its bet id, so completion order does not matter
+    let detail_results: Vec<(BetID, Result<Option<BetDetails>>)> = stream::iter(&bets)
+        .map(|bet| async move {
+            let request = BetDetailsRequest::from(bet);
+            (
+                bet.id,
+                extend_bet_with_details(state, &request, provider).await,
+            )
+        })
+        .buffer_unordered(state.options.details_concurrency)
+        .collect()
+        .await;
+
+    let mut bet_details = vec![];
+    let mut failed_bets = vec![];
+    let mut details_failed = 0;
+
+    for (bet_id, result) in detail_results {
+        match result {
+            Ok(Some(detail)) => bet_details.push(detail),
+            Ok(None) => {}
+            Err(e) => {
+                let error = format!("{:#}", e);
+
+                // Skipped requests are counted and reported by the circuit breaker,
+                // disabled connectors are reported once when connectors are loaded
+                if !e.is::<CircuitOpen>() && !e.is::<ConnectorDisabled>() {
+                    details_failed += 1;
+                    log_details_failure(&state.pg, bet_id, provider, e).await;
+                }
+
+                if let Some(bet) = bets.iter().find(|bet| bet.id == bet_id) {
+                    failed_bets.push((bet, error));
+                }
+            }
         }
     }
 
-    if bet_details.len() > 0 {
+    let details_fetched = bet_details.len() as u64;
+
+    if !bet_details.is_empty() {
         insert_bet_details_to_details_table(&state.maria_db, bet_details).await?;
     }
 
-    save_all(
+    let bets_archived = bet_ids.len() as u64;
+
+    let (debts_upserted, opening_balance_rows_updated) = save_all(
         pg_transaction,
         provider,
-        create_credit_debt_models(debts, state)?,
+        credit_debts,
         bet_ids,
         wl_by_date_by_user,
     )
     .await?;
 
+    // Retried by later runs, the bet itself is already gone from PG
+    enqueue_pending_details(pg_transaction, provider, failed_bets).await?;
+
+    let mut stats = state.stats.lock().unwrap();
+    let provider_stats = stats.provider(provider);
+
+    provider_stats.bets_archived += bets_archived;
+    provider_stats.details_fetched += details_fetched;
+    provider_stats.details_failed += details_failed;
+    provider_stats.debts_upserted += debts_upserted;
+    provider_stats.opening_balance_rows_updated += opening_balance_rows_updated;
+    provider_stats.duration += started_at.elapsed();
+
+    metrics::observe_chunk(provider, started_at, bets_archived as usize);
+
     Ok(())
 }
 
@@ -106,20 +208,23 @@ async fn save_all(
     debts: FxHashMap<Date, SmallVec<[CreditDebt; DEBT_SIZE]>>,
     bet_ids: ArrayVec<BetID, CHUNK_SIZE>,
     wl_by_date_by_user: WlByDateByUser,
-) -> Result<()> {
+) -> Result<(u64, u64)> {
+    // Concurrent providers update the same debt and opening balance rows.
+    // Serializing these writes avoids deadlocks on rows locked in different order.
+    lock_figures(pg_transaction).await?;
+
+    let mut debts_upserted = 0;
+    let mut opening_balance_rows_updated = 0;
+
     for (date, debts) in debts.into_iter() {
-        save_debts(pg_transaction, debts, date).await?;
+        debts_upserted += save_debts(pg_transaction, debts, date).await?;
     }
 
     delete_bets_by_ids(bet_ids, provider_or_bet_type, pg_transaction).await?;
 
-    let current_start_of_month = OffsetDateTime::now_utc().date().replace_day(1).unwrap();
-
     for (date, wl_by_user) in wl_by_date_by_user.into_iter() {
-        let mut start_of_month_table = date.clone().replace_day(1).unwrap();
-
-        loop {
-            update_opening_balance_amount(
+        for start_of_month_table in opening_balance_months(date) {
+            opening_balance_rows_updated += update_opening_balance_amount(
                 pg_transaction,
                 get_archive_schema_name(start_of_month_table),
                 get_dynamic_table_name(OPENING_BALANCE_TABLE_NAME, start_of_month_table),
@@ -127,14 +232,48 @@ async fn save_all(
                 &wl_by_user,
             )
             .await?;
+        }
+    }
+
+    Ok((debts_upserted, opening_balance_rows_updated))
+}
 
-            start_of_month_table = add_month(start_of_month_table);
+/// WL of a figures date is propagated to every later opening balance record,
+/// up to the table of the current month
+fn opening_balance_months(date: Date) -> Vec<Date> {
+    let current_start_of_month = OffsetDateTime::now_utc().date().replace_day(1).unwrap();
+    let mut start_of_month_table = date.replace_day(1).unwrap();
+    let mut months = vec![];
 
-            if start_of_month_table > current_start_of_month {
-                break;
-            }
+    loop {
+        months.push(start_of_month_table);
+        start_of_month_table = add_month(start_of_month_table);
+
+        if start_of_month_table > current_start_of_month {
+            return months;
         }
     }
+}
 
-    Ok(())
+/// Opening balance rows which `save_all` would update, by month table
+async fn count_opening_balance_rows_by_month(
+    pg_transaction: &mut Transaction<'_, sqlx::Postgres>,
+    wl_by_date_by_user: &WlByDateByUser,
+) -> Result<FxHashMap<Date, u64>> {
+    let mut rows_by_month: FxHashMap<Date, u64> = FxHashMap::default();
+
+    for (date, wl_by_user) in wl_by_date_by_user {
+        for start_of_month_table in opening_balance_months(*date) {
+            *rows_by_month.entry(start_of_month_table).or_default() += count_opening_balance_rows(
+                pg_transaction,
+                get_archive_schema_name(start_of_month_table),
+                get_dynamic_table_name(OPENING_BALANCE_TABLE_NAME, start_of_month_table),
+                *date,
+                wl_by_user.keys().copied().collect(),
+            )
+            .await?;
+        }
+    }
+
+    Ok(rows_by_month)
 }
@@ -0,0 +1,261 @@
+use std::str::FromStr;
+
+use anyhow::{Context, Result};
+use futures::{stream, StreamExt};
+use sqlx::{FromRow, PgPool, Postgres, QueryBuilder, Transaction};
+use time::{Duration, OffsetDateTime};
+
+use crate::{
+    archiver::CHUNK_SIZE,
+    connectors::ConnectorDisabled,
+    enums::provider::GameProvider,
+    helpers::{logger::log_warning, State},
+    types::BetID,
+};
+
+use super::{
+    details::extend_bet_with_details,
+    loader::{insert_bet_details_to_details_table, Bet, BetDetails, BetDetailsRequest},
+};
+
+const FIRST_RETRY_DELAY_MINUTES: i64 = 5;
+const MAX_RETRY_DELAY_MINUTES: i64 = 24 * 60;
+
+/// Bet which details could not be fetched while it was archived.
+/// The bet itself is already in MariaDB, only the connector input is kept.
+#[derive(FromRow)]
+struct PendingBetDetails {
+    provider: String,
+    attempts: i32,
+    #[sqlx(flatten)]
+    request: BetDetailsRequest,
+}
+
+/// Delay doubles with every failed attempt
+fn next_attempt_at(attempts: i32) -> OffsetDateTime {
+    let delay = FIRST_RETRY_DELAY_MINUTES
+        .saturating_mul(1 << attempts.clamp(0, 20))
+        .min(MAX_RETRY_DELAY_MINUTES);
+
+    OffsetDateTime::now_utc() + Duration::minutes(delay)
+}
+
+/// Must be executed in the same transaction as bet deletion,
+/// so details are queued only for bets which actually left PG
+pub async fn enqueue_pending_details(
+    pg_transaction: &mut Transaction<'_, Postgres>,
+    provider: GameProvider,
+    failed: Vec<(&Bet, String)>,
+) -> Result<()> {
+    if failed.is_empty() {
+        return Ok(());
+    }
+
+    let now = OffsetDateTime::now_utc();
+
+    let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new(
+        r#"
+            INSERT INTO public.pending_bet_details (
+                bet_id,
+                provider,
+                provider_bet_id,
+                user_id,
+                username,
+                currency,
+                details,
+                transactions,
+                provider_game_vendor_id,
+                attempts,
+                next_attempt_at,
+                last_error,
+                created_at
+            )
+        "#,
+    );
+
+    query_builder.push_values(failed, |mut b, (bet, error)| {
+        b.push_bind(bet.id)
+            .push_bind(provider.to_string())
+            .push_bind(bet.provider_bet_id.clone())
+            .push_bind(bet.user_id)
+            .push_bind(bet.username.clone())
+            .push_bind(bet.currency.clone())
+            .push_bind(bet.details.clone())
+            .push_bind(bet.transactions.clone())
+            .push_bind(bet.provider_game_vendor_id.clone())
+            .push_bind(0)
+            .push_bind(next_attempt_at(0))
+            .push_bind(error)
+            .push_bind(now);
+    });
+
+    // Bet of an abandoned chunk may be queued again on the next run
+    query_builder.push(" ON CONFLICT (bet_id) DO NOTHING");
+
+    query_builder
+        .build()
+        .execute(&mut **pg_transaction)
+        .await
+        .context("Failed to enqueue pending bet details")?;
+
+    Ok(())
+}
+
+/// Fetches details which are due for retry and writes them to the MariaDB details table.
+/// Entries older than `max_age` are dropped, the provider is not expected to have them anymore.
+pub async fn retry_pending_details(state: &State, max_age: Duration) -> Result<()> {
+    drop_expired_pending_details(&state.pg, max_age).await?;
+
+    // Failed entries are rescheduled after this moment, so every entry is tried once per call
+    let started_at = OffsetDateTime::now_utc();
+
+    loop {
+        let pending: Vec<PendingBetDetails> = sqlx::query_as(
+            r#"
+                SELECT
+                    bet_id,
+                    provider,
+                    provider_bet_id,
+                    user_id,
+                    username,
+                    currency,
+                    details,
+                    transactions,
+                    provider_game_vendor_id,
+                    attempts
+                FROM public.pending_bet_details
+                WHERE next_attempt_at <= $1 AND dead_at IS NULL
+                ORDER BY next_attempt_at
+                LIMIT $2
+            "#,
+        )
+        .bind(started_at)
+        .bind(CHUNK_SIZE as i64)
+        .fetch_all(&state.pg)
+        .await
+        .context("Failed to fetch pending bet details")?;
+
+        if pending.is_empty() {
+            return Ok(());
+        }
+
+        let mut requests = vec![];
+
+        for pending in pending {
+            match GameProvider::from_str(&pending.provider) {
+                Ok(provider) => requests.push((provider, pending.attempts, pending.request)),
+                Err(_) => {
+                    let reason = format!("Unknown provider '{}'", pending.provider);
+                    mark_dead(&state.pg, pending.request.id, reason).await?;
+                }
+            }
+        }
+
+        let results: Vec<(GameProvider, i32, BetID, Result<Option<BetDetails>>)> =
+            stream::iter(&requests)
+                .map(|(provider, attempts, request)| async move {
+                    let result = extend_bet_with_details(state, request, *provider).await;
+                    (*provider, *attempts, request.id, result)
+                })
+                .buffer_unordered(state.options.details_concurrency)
+                .collect()
+                .await;
+
+        let mut bet_details = vec![];
+        let mut done_ids = vec![];
+
+        for (provider, attempts, bet_id, result) in results {
+            match result {
+                Ok(details) => {
+                    if let Some(details) = details {
+                        state
+                            .stats
+                            .lock()
+                            .unwrap()
+                            .provider(provider)
+                            .details_fetched += 1;
+                        bet_details.push(details);
+                    }
+
+                    done_ids.push(bet_id.0);
+                }
+                // Not an attempt, the entry waits until the connector is loaded again
+                Err(e) if e.is::<ConnectorDisabled>() => {
+                    reschedule(&state.pg, bet_id, attempts, format!("{:#}", e)).await?
+                }
+                Err(e) => reschedule(&state.pg, bet_id, attempts + 1, format!("{:#}", e)).await?,
+            }
+        }
+
+        if !bet_details.is_empty() {
+            insert_bet_details_to_details_table(&state.maria_db, bet_details).await?;
+        }
+
+        sqlx::query("DELETE FROM public.pending_bet_details WHERE bet_id = ANY($1)")
+            .bind(done_ids)
+            .execute(&state.pg)
+            .await
+            .context("Failed to delete retried pending bet details")?;
+    }
+}
+
+async fn reschedule(pg: &PgPool, bet_id: BetID, attempts: i32, error: String) -> Result<()> {
+    sqlx::query(
+        r#"
+            UPDATE public.pending_bet_details
+            SET attempts = $1, next_attempt_at = $2, last_error = $3
+            WHERE bet_id = $4
+        "#,
+    )
+    .bind(attempts)
+    .bind(next_attempt_at(attempts))
+    .bind(error)
+    .bind(bet_id)
+    .execute(pg)
+    .await
+    .with_context(|| format!("Failed to reschedule pending details of bet '{bet_id}'"))?;
+
+    Ok(())
+}
+
+/// Entry which can never be retried is kept for investigation until it expires
+async fn mark_dead(pg: &PgPool, bet_id: BetID, reason: String) -> Result<()> {
+    sqlx::query(
+        r#"
+            UPDATE public.pending_bet_details
+            SET dead_at = $1, last_error = $2
+            WHERE bet_id = $3
+        "#,
+    )
+    .bind(OffsetDateTime::now_utc())
+    .bind(&reason)
+    .bind(bet_id)
+    .execute(pg)
+    .await
+    .with_context(|| format!("Failed to mark pending details of bet '{bet_id}' as dead"))?;
+
+    log_warning(
+        pg,
+        format!("Stopped retrying details of bet '{bet_id}': {reason}"),
+    )
+    .await
+}
+
+async fn drop_expired_pending_details(pg: &PgPool, max_age: Duration) -> Result<()> {
+    let dropped = sqlx::query("DELETE FROM public.pending_bet_details WHERE created_at < $1")
+        .bind(OffsetDateTime::now_utc() - max_age)
+        .execute(pg)
+        .await
+        .context("Failed to drop expired pending bet details")?
+        .rows_affected();
+
+    if dropped > 0 {
+        log_warning(
+            pg,
+            format!("Gave up fetching details of {dropped} bets older than {max_age}"),
+        )
+        .await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,106 @@
+use anyhow::Result;
+use arrayvec::ArrayVec;
+use log::error;
+use rustc_hash::FxHashMap;
+use serde_json::json;
+
+use crate::{
+    enums::provider::GameProvider,
+    helpers::{logger::log_warning_with_payload, State},
+    types::{BetID, ChunkVec},
+};
+
+use super::loader::{delete_maria_db_bets_by_ids, get_maria_db_bets_by_ids, ArchivedBet, Bet};
+
+/// Returns only bets whose MariaDB copy matches the PG row.
+/// Rejected bets are kept in PG, so their MariaDB copies are removed
+/// to not count them twice, and the whole chunk is reported with one warning.
+pub async fn keep_verified_bets(
+    state: &State,
+    provider: GameProvider,
+    bets: ChunkVec<Bet>,
+) -> Result<ChunkVec<Bet>> {
+    let bet_ids: Vec<BetID> = bets.iter().map(|bet| bet.id).collect();
+
+    let archived_by_id: FxHashMap<String, ArchivedBet> =
+        get_maria_db_bets_by_ids(&state.maria_db, &bet_ids)
+            .await?
+            .into_iter()
+            .map(|archived| (archived.id.clone(), archived))
+            .collect();
+
+    let mut verified: ChunkVec<Bet> = ArrayVec::new();
+    let mut rejected: Vec<(BetID, String)> = vec![];
+
+    for bet in bets {
+        match find_mismatch(&bet, archived_by_id.get(&bet.id.to_string())) {
+            None => verified.push(bet),
+            Some(reason) => rejected.push((bet.id, reason)),
+        }
+    }
+
+    if rejected.is_empty() {
+        return Ok(verified);
+    }
+
+    let rejected_ids: Vec<BetID> = rejected.iter().map(|(id, _)| *id).collect();
+    delete_maria_db_bets_by_ids(&state.maria_db, &rejected_ids).await?;
+
+    // Rejected bets are safe in PG, a failed log must not stop the chunk
+    let logged = log_warning_with_payload(
+        &state.pg,
+        format!(
+            "{} bets of '{}' were kept in PG, their MariaDB copies don't match",
+            rejected.len(),
+            provider
+        ),
+        json!(rejected
+            .iter()
+            .map(|(id, reason)| json!({ "bet_id": id, "reason": reason }))
+            .collect::<Vec<_>>()),
+    )
+    .await;
+
+    if let Err(e) = logged {
+        error!("{:?}", e);
+    }
+
+    Ok(verified)
+}
+
+fn find_mismatch(bet: &Bet, archived: Option<&ArchivedBet>) -> Option<String> {
+    let Some(archived) = archived else {
+        return Some("not found in MariaDB".to_string());
+    };
+
+    if archived.stake != bet.stake {
+        return Some(format!(
+            "stake mismatch, PG: {}, MariaDB: {}",
+            bet.stake, archived.stake
+        ));
+    }
+
+    if archived.wl != bet.wl {
+        return Some(format!(
+            "wl mismatch, PG: {:?}, MariaDB: {:?}",
+            bet.wl, archived.wl
+        ));
+    }
+
+    if archived.status != bet.status.to_string() {
+        return Some(format!(
+            "status mismatch, PG: {}, MariaDB: {}",
+            bet.status, archived.status
+        ));
+    }
+
+    if archived.funds_delta() != bet.funds_delta {
+        return Some(format!(
+            "funds delta mismatch, PG: {:?}, MariaDB: {:?}",
+            bet.funds_delta,
+            archived.funds_delta()
+        ));
+    }
+
+    None
+}
@@ -0,0 +1,120 @@
+use std::collections::BTreeMap;
+
+use rustc_hash::FxHashMap;
+use smallvec::SmallVec;
+use time::Date;
+
+use crate::{
+    enums::provider::GameProvider,
+    types::{Currency, UserID, Username},
+};
+
+use super::bets::{loader::CreditDebt, DEBT_SIZE};
+
+/// Collects figures which would be written by a real run
+#[derive(Debug, Default)]
+pub struct DryRunReport {
+    bets_by_provider: FxHashMap<GameProvider, usize>,
+    wl_by_date: BTreeMap<Date, WlDelta>,
+    debts: FxHashMap<(Date, UserID), DebtRow>,
+    /// Opening balance rows updated with WL deltas, by month table
+    opening_balance_rows: BTreeMap<Date, u64>,
+    /// Lottery bets of unknown kind, which are left in PG
+    pub unmatched_lottery_bets: i64,
+}
+
+#[derive(Debug, Default)]
+struct WlDelta {
+    amount: i64,
+    users: usize,
+}
+
+#[derive(Debug)]
+struct DebtRow {
+    username: Username,
+    currency: Currency,
+    amount: i64,
+}
+
+impl DryRunReport {
+    pub fn record(
+        &mut self,
+        provider: GameProvider,
+        bets_count: usize,
+        wl_by_date_by_user: &FxHashMap<Date, FxHashMap<UserID, i64>>,
+        debts: &FxHashMap<Date, SmallVec<[CreditDebt; DEBT_SIZE]>>,
+        opening_balance_rows: &FxHashMap<Date, u64>,
+    ) {
+        *self.bets_by_provider.entry(provider).or_default() += bets_count;
+
+        for (date, wl_by_user) in wl_by_date_by_user {
+            let delta = self.wl_by_date.entry(*date).or_default();
+            delta.amount += wl_by_user.values().sum::<i64>();
+            delta.users += wl_by_user.len();
+        }
+
+        // Debts are upserted with 'debt_amount + EXCLUDED.debt_amount', so chunks add up
+        for (date, debts) in debts {
+            for debt in debts {
+                self.debts
+                    .entry((*date, debt.user_id))
+                    .and_modify(|row| row.amount += debt.debt_amount)
+                    .or_insert_with(|| DebtRow {
+                        username: debt.username.clone(),
+                        currency: debt.currency.clone(),
+                        amount: debt.debt_amount,
+                    });
+            }
+        }
+
+        for (month, rows) in opening_balance_rows {
+            *self.opening_balance_rows.entry(*month).or_default() += rows;
+        }
+    }
+
+    pub fn print(&self) {
+        let mut providers: Vec<(&GameProvider, &usize)> = self.bets_by_provider.iter().collect();
+        providers.sort_by(|(a, _), (b, _)| a.as_ref().cmp(b.as_ref()));
+
+        println!("Bets to archive by provider:");
+
+        for (provider, count) in providers {
+            println!("  {provider}: {count}");
+        }
+
+        println!("WL deltas by figures date:");
+
+        for (date, delta) in &self.wl_by_date {
+            println!("  {date}: {} ({} users)", delta.amount, delta.users);
+        }
+
+        if self.unmatched_lottery_bets > 0 {
+            println!(
+                "  lottery bets of unknown kind, not archived: {}",
+                self.unmatched_lottery_bets
+            );
+        }
+
+        println!("Opening balance rows to update by month table:");
+
+        for (month, rows) in &self.opening_balance_rows {
+            println!("  {}-{:02}: {rows}", month.year(), month.month() as u8);
+        }
+
+        let mut debts: Vec<(&(Date, UserID), &DebtRow)> = self.debts.iter().collect();
+        debts.sort_by(|((a_date, _), a), ((b_date, _), b)| {
+            a_date
+                .cmp(b_date)
+                .then_with(|| a.username.0.cmp(&b.username.0))
+        });
+
+        println!("Credit debts to upsert:");
+
+        for ((date, user_id), row) in debts {
+            println!(
+                "  {date} {} ({user_id}): {} {}",
+                row.username, row.amount, row.currency.0
+            );
+        }
+    }
+}
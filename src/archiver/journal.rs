@@ -0,0 +1,159 @@
+use std::str::FromStr;
+
+use anyhow::{Context, Result};
+use sqlx::{PgPool, Row};
+use strum_macros::{AsRefStr, EnumString};
+use time::{Date, OffsetDateTime};
+use uuid::Uuid;
+
+use crate::helpers::logger::log_warning;
+
+/// Stage of the whole run. Stages only move forward, so an interrupted run
+/// resumes from the stage it has not finished yet. Chunks need no journal:
+/// a chunk leaves PG in the transaction which applies its figures, and MariaDB
+/// copies of an interrupted chunk are replaced when it is archived again.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, AsRefStr, EnumString)]
+#[strum(serialize_all = "snake_case")]
+pub enum RunStage {
+    Bets,
+    DetailsSync,
+    Done,
+    /// Replaced by a run with another cutoff date before it was finished
+    Abandoned,
+}
+
+#[derive(Debug)]
+pub struct ArchiveRun {
+    pub id: Uuid,
+    pub stage: RunStage,
+}
+
+/// Returns the last unfinished run of the same window or starts a new one.
+/// Unfinished runs of other windows are abandoned, their bets are still in PG
+/// and committed chunks are already complete.
+pub async fn start_or_resume_run(
+    pg: &PgPool,
+    start_date: Option<Date>,
+    cutoff_date: Date,
+) -> Result<ArchiveRun> {
+    abandon_other_runs(pg, start_date, cutoff_date).await?;
+
+    let unfinished = sqlx::query(
+        r#"
+            SELECT
+                id,
+                stage
+            FROM public.archive_run
+            WHERE stage NOT IN ($1, $2)
+                AND cutoff_date = $3
+                AND start_date IS NOT DISTINCT FROM $4
+            ORDER BY started_at DESC
+            LIMIT 1
+        "#,
+    )
+    .bind(RunStage::Done.as_ref())
+    .bind(RunStage::Abandoned.as_ref())
+    .bind(cutoff_date)
+    .bind(start_date)
+    .fetch_optional(pg)
+    .await
+    .context("Failed to fetch unfinished archive run")?;
+
+    if let Some(row) = unfinished {
+        let id: Uuid = row.try_get("id")?;
+        let stage: String = row.try_get("stage")?;
+
+        return Ok(ArchiveRun {
+            id,
+            stage: RunStage::from_str(&stage)
+                .with_context(|| format!("Unknown archive run stage: '{stage}'"))?,
+        });
+    }
+
+    let id: Uuid = sqlx::query_scalar(
+        r#"
+            INSERT INTO public.archive_run (start_date, cutoff_date, stage, started_at)
+            VALUES ($1, $2, $3, $4)
+            RETURNING id
+        "#,
+    )
+    .bind(start_date)
+    .bind(cutoff_date)
+    .bind(RunStage::Bets.as_ref())
+    .bind(OffsetDateTime::now_utc())
+    .fetch_one(pg)
+    .await
+    .context("Failed to create archive run")?;
+
+    Ok(ArchiveRun {
+        id,
+        stage: RunStage::Bets,
+    })
+}
+
+pub async fn set_run_stage(pg: &PgPool, run_id: Uuid, stage: RunStage) -> Result<()> {
+    let finished_at = (stage == RunStage::Done).then(OffsetDateTime::now_utc);
+
+    sqlx::query(
+        r#"
+            UPDATE public.archive_run
+            SET stage = $1, finished_at = $2
+            WHERE id = $3
+        "#,
+    )
+    .bind(stage.as_ref())
+    .bind(finished_at)
+    .bind(run_id)
+    .execute(pg)
+    .await
+    .with_context(|| format!("Failed to set archive run stage to '{}'", stage.as_ref()))?;
+
+    Ok(())
+}
+
+async fn abandon_other_runs(
+    pg: &PgPool,
+    start_date: Option<Date>,
+    cutoff_date: Date,
+) -> Result<()> {
+    let abandoned: Vec<(Uuid, Option<Date>, Date, String)> = sqlx::query_as(
+        r#"
+            UPDATE public.archive_run AS r
+            SET stage = $1, finished_at = $2
+            FROM public.archive_run AS old
+            WHERE r.id = old.id
+                AND r.stage NOT IN ($1, $3)
+                AND (r.cutoff_date <> $4 OR r.start_date IS DISTINCT FROM $5)
+            RETURNING r.id, r.start_date, r.cutoff_date, old.stage
+        "#,
+    )
+    .bind(RunStage::Abandoned.as_ref())
+    .bind(OffsetDateTime::now_utc())
+    .bind(RunStage::Done.as_ref())
+    .bind(cutoff_date)
+    .bind(start_date)
+    .fetch_all(pg)
+    .await
+    .context("Failed to abandon unfinished archive runs")?;
+
+    for (id, run_start_date, run_cutoff_date, stage) in abandoned {
+        log_warning(
+            pg,
+            format!(
+                "Abandoned unfinished archive run '{id}' of {} at stage '{stage}', requested window is {}",
+                format_window(run_start_date, run_cutoff_date),
+                format_window(start_date, cutoff_date),
+            ),
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
+fn format_window(start_date: Option<Date>, cutoff_date: Date) -> String {
+    match start_date {
+        Some(start_date) => format!("{start_date}..{cutoff_date}"),
+        None => format!("..{cutoff_date}"),
+    }
+}
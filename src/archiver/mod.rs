@@ -1,29 +1,130 @@
 pub mod bets;
+pub mod dry_run;
+pub mod journal;
 pub mod opening_balance;
+pub mod options;
+pub mod retention;
+pub mod summary;
+
+use std::time::Instant;
 
 use anyhow::{Context, Result};
+use clap::Parser;
+use futures::{stream, TryStreamExt};
+use log::{error, info};
+use rustc_hash::FxHashMap;
+use sqlx::{MySqlPool, PgPool};
 use strum::VariantArray;
 
 use crate::{
+    cli::{Cli, Command},
     connectors,
     consts::BET_DETAIL_REPORT_TABLE_NAME,
     db,
     enums::provider::{
         GameProvider, LiveCasinoProvider, Lottery, OnlineCasinoProvider, SlotProvider, Sportsbook,
     },
-    helpers::{logger::log_error, query_helper::get_bet_table_name, State},
+    helpers::{
+        logger::{init_logger, log_error, log_warning},
+        metrics,
+        query_helper::get_bet_table_name,
+        State,
+    },
+    types::ChunkVec,
 };
 
 pub const CHUNK_SIZE: usize = 1500;
 
 use self::bets::{
     handle_bet_chunk,
-    loader::{get_target_data_bench, truncate_maria_db_table, update_bet_details},
+    loader::{
+        count_unmatched_lottery_bets, get_target_data_bench, truncate_maria_db_table,
+        update_bet_details, Bet,
+    },
+    pending_details,
 };
+use self::journal::{set_run_stage, start_or_resume_run, RunStage};
+use self::options::RunOptions;
+use self::retention::StatusCutoff;
+use self::summary::{finish_run_summary, print_recent_runs, start_run_summary};
 
 pub async fn run(state: &mut State) -> Result<()> {
-    opening_balance::create_opening_balance_records(state).await?;
+    if let Some(start_date) = state.options.start_date {
+        opening_balance::ensure_opening_balance_history(&state.pg, start_date).await?;
+    }
+
+    if state.options.dry_run {
+        opening_balance::load_credit_players(state).await?;
+        return archive_all_providers(state).await;
+    }
+
+    let archive_run = start_or_resume_run(
+        &state.pg,
+        state.options.start_date,
+        state.options.cutoff_date,
+    )
+    .await?;
+    state.run_id = Some(archive_run.id);
+
+    if archive_run.stage == RunStage::Bets {
+        opening_balance::create_opening_balance_records(state).await?;
+        archive_all_providers(state).await?;
+        set_run_stage(&state.pg, archive_run.id, RunStage::DetailsSync).await?;
+    }
+
+    retry_pending_details(state).await?;
+
+    // Both queries are idempotent, so an interrupted sync is simply repeated
+    sync_bet_details(&state.maria_db).await?;
+    set_run_stage(&state.pg, archive_run.id, RunStage::Done).await
+}
+
+/// Bets of one table archived by a single worker
+struct ArchiveJob {
+    table: String,
+    /// Provider of the table. Lottery bets are attributed to their own kind.
+    provider: GameProvider,
+    lottery_kinds: Option<Vec<String>>,
+    cutoffs: Vec<StatusCutoff>,
+}
 
+impl ArchiveJob {
+    fn new(provider: GameProvider, options: &RunOptions) -> Self {
+        Self {
+            table: get_bet_table_name(provider),
+            provider,
+            lottery_kinds: None,
+            cutoffs: options
+                .retention
+                .status_cutoffs(provider, options.cutoff_date),
+        }
+    }
+
+    /// Every lottery is stored in the shared lottery table. Kinds with the same
+    /// retention are archived in a single pass instead of one pass per kind.
+    fn lottery_jobs(options: &RunOptions) -> Vec<Self> {
+        let mut jobs: Vec<Self> = vec![];
+
+        for lottery in Lottery::VARIANTS {
+            let job = Self::new(lottery.into_game_provider(), options);
+
+            match jobs.iter_mut().find(|j| j.cutoffs == job.cutoffs) {
+                Some(existing) => existing
+                    .lottery_kinds
+                    .get_or_insert_with(Vec::new)
+                    .push(lottery.to_string()),
+                None => jobs.push(Self {
+                    lottery_kinds: Some(vec![lottery.to_string()]),
+                    ..job
+                }),
+            }
+        }
+
+        jobs
+    }
+}
+
+async fn archive_all_providers(state: &State) -> Result<()> {
     let providers: Vec<GameProvider> = [
         LiveCasinoProvider::VARIANTS
             .into_iter()
@@ -37,10 +138,6 @@ pub async fn run(state: &mut State) -> Result<()> {
             .into_iter()
             .map(|p| p.into_game_provider())
             .collect(),
-        Lottery::VARIANTS
-            .into_iter()
-            .map(|p| p.into_game_provider())
-            .collect(),
         Sportsbook::VARIANTS
             .into_iter()
             .map(|p| p.into_game_provider())
@@ -48,49 +145,257 @@ pub async fn run(state: &mut State) -> Result<()> {
     ]
     .concat();
 
-    'provider_bet_for: for provider in providers {
-        let runtime_table_name = get_bet_table_name(provider);
+    let mut jobs: Vec<ArchiveJob> = providers
+        .into_iter()
+        .map(|provider| ArchiveJob::new(provider, &state.options))
+        .collect();
 
-        loop {
-            let bet_chunk = get_target_data_bench(&state.pg, &runtime_table_name, None).await?;
+    jobs.extend(ArchiveJob::lottery_jobs(&state.options));
 
-            if bet_chunk.len() == 0 {
-                continue 'provider_bet_for;
-            }
+    stream::iter(jobs.into_iter().map(Ok))
+        .try_for_each_concurrent(state.options.concurrency, |job| archive_job(job, state))
+        .await?;
 
-            let mut pg_transaction = state
-                .pg
-                .begin()
-                .await
-                .context("Failed to start PG transaction")?;
+    report_unmatched_lottery_bets(state).await
+}
+
+/// Lottery jobs fetch bets by kind, bets of unknown kinds would stay in PG unnoticed
+async fn report_unmatched_lottery_bets(state: &State) -> Result<()> {
+    let count = count_unmatched_lottery_bets(
+        &state.pg,
+        state.options.start_date,
+        state.options.cutoff_date,
+    )
+    .await?;
+
+    if count == 0 {
+        return Ok(());
+    }
+
+    if state.options.dry_run {
+        state.dry_run_report.lock().unwrap().unmatched_lottery_bets = count;
+        return Ok(());
+    }
+
+    log_warning(
+        &state.pg,
+        format!("{count} lottery bets have no known lottery kind and are not archived"),
+    )
+    .await
+}
+
+pub async fn archive_provider(provider: GameProvider, state: &State) -> Result<()> {
+    let mut job = ArchiveJob::new(provider, &state.options);
+
+    if let GameProvider::Lottery(lottery) = provider {
+        job.lottery_kinds = Some(vec![lottery.to_string()]);
+    }
+
+    archive_job(job, state).await
+}
+
+async fn archive_job(job: ArchiveJob, state: &State) -> Result<()> {
+    let mut after = None;
+
+    loop {
+        let bet_chunk = get_target_data_bench(
+            &state.pg,
+            &job.table,
+            job.lottery_kinds.as_deref(),
+            state.options.start_date,
+            &job.cutoffs,
+            state.options.chunk_size,
+            after,
+        )
+        .await?;
+
+        if bet_chunk.is_empty() {
+            return Ok(());
+        }
 
-            handle_bet_chunk(provider, bet_chunk, state, &mut pg_transaction).await?;
+        after = bet_chunk.last().map(|bet| (bet.last_status_change, bet.id));
 
+        let mut pg_transaction = state
+            .pg
+            .begin()
+            .await
+            .context("Failed to start PG transaction")?;
+
+        for (provider, bets) in group_by_provider(job.provider, bet_chunk) {
+            handle_bet_chunk(provider, bets, state, &mut pg_transaction).await?;
+        }
+
+        if state.options.dry_run {
             pg_transaction
-                .commit()
+                .rollback()
                 .await
-                .context("Failed to commit transaction on bet chunk")?;
+                .context("Failed to rollback dry run transaction on bet chunk")?;
+
+            continue;
         }
+
+        pg_transaction
+            .commit()
+            .await
+            .context("Failed to commit transaction on bet chunk")?;
     }
+}
 
-    update_bet_details(&state.maria_db).await?;
-    truncate_maria_db_table(&state.maria_db, BET_DETAIL_REPORT_TABLE_NAME).await?;
+/// Splits a lottery chunk by kind, so figures and details are attributed per lottery
+fn group_by_provider(
+    table_provider: GameProvider,
+    bets: ChunkVec<Bet>,
+) -> FxHashMap<GameProvider, ChunkVec<Bet>> {
+    let mut bets_by_provider: FxHashMap<GameProvider, ChunkVec<Bet>> = FxHashMap::default();
 
-    Ok(())
+    for bet in bets {
+        let provider = bet
+            .kind
+            .map(GameProvider::Lottery)
+            .unwrap_or(table_provider);
+
+        bets_by_provider.entry(provider).or_default().push(bet);
+    }
+
+    bets_by_provider
+}
+
+async fn retry_pending_details(state: &State) -> Result<()> {
+    let max_age = time::Duration::days(state.options.details_max_age_days);
+    pending_details::retry_pending_details(state, max_age).await
+}
+
+pub async fn sync_bet_details(maria_db: &MySqlPool) -> Result<()> {
+    update_bet_details(maria_db).await?;
+    truncate_maria_db_table(maria_db, BET_DETAIL_REPORT_TABLE_NAME).await
 }
 
 pub async fn launch() {
+    let cli = Cli::parse();
+
     dotenvy::dotenv().expect("Failed to parse .env");
-    env_logger::init();
+    init_logger(cli.log_format);
+
+    let pool_size = db::pool_size(cli.command.concurrency());
+    let pg = db::create_pg_connection(pool_size).await;
+
+    if let Command::Status { limit } = cli.command {
+        if let Err(e) = print_recent_runs(&pg, limit).await {
+            error!("{:?}", e);
+            std::process::exit(1);
+        }
+
+        return;
+    }
+
+    if let Command::ValidateConfig = cli.command {
+        match connectors::load_connectors(&pg, true).await {
+            Ok(_) => println!("Provider configs are valid"),
+            Err(e) => {
+                error!("{:?}", e);
+                std::process::exit(1);
+            }
+        }
+
+        return;
+    }
+
+    let mysql = db::create_mysql_connection(pool_size).await;
+
+    if let Some(addr) = cli.metrics_addr.clone() {
+        tokio::spawn(async move {
+            if let Err(e) = metrics::serve(&addr).await {
+                error!("Metrics server stopped: {:?}", e);
+            }
+        });
+    }
+
+    loop {
+        info!("Started '{}'", cli.command.as_ref());
+
+        match execute_with_fresh_state(&cli, &pg, &mysql).await {
+            Ok(()) => info!("Finished '{}'", cli.command.as_ref()),
+            Err(e) => {
+                error!("{:?}", e);
+
+                // The next repetition of a daemon must not be stopped by a failed log
+                if let Err(e) = log_error(&pg, e).await {
+                    error!("{:?}", e);
+                }
+            }
+        }
+
+        if let Some(path) = &cli.metrics_file {
+            if let Err(e) = metrics::write_textfile(path) {
+                error!("{:?}", e);
+            }
+        }
+
+        let Some(minutes) = cli.interval_minutes else {
+            break;
+        };
+
+        tokio::time::sleep(std::time::Duration::from_secs(minutes * 60)).await;
+    }
+}
+
+/// Connectors and caches are rebuilt for every repetition in daemon mode,
+/// so config changes are picked up and no player data outlives its run
+async fn execute_with_fresh_state(cli: &Cli, pg: &PgPool, mysql: &MySqlPool) -> Result<()> {
+    let connectors = connectors::load_connectors(pg, cli.strict_config).await?;
+    let mut state = State::new(connectors, pg.clone(), mysql.clone());
+
+    execute(cli.command.clone(), &mut state).await
+}
+
+async fn execute(command: Command, state: &mut State) -> Result<()> {
+    if let Some(options) = command.run_options()? {
+        state.options = options;
+    }
 
-    let pg = db::create_pg_connection().await;
-    let mysql = db::create_mysql_connection().await;
+    if state.options.dry_run {
+        execute_stage(command, state).await?;
+        state.dry_run_report.lock().unwrap().print();
 
-    let connectors = connectors::load_connectors(&pg).await.unwrap();
-    let mut state = State::new(connectors, pg, mysql);
+        return Ok(());
+    }
+
+    let summary_id = start_run_summary(&state.pg, command.as_ref()).await?;
+    let started_at = Instant::now();
+    let result = execute_stage(command, state).await;
+    metrics::observe_run(started_at, result.is_ok());
+
+    let stats = std::mem::take(&mut *state.stats.lock().unwrap());
+
+    // A failed summary must not hide the outcome of the run itself
+    if let Err(e) = finish_run_summary(&state.pg, summary_id, state.run_id, &result, stats).await {
+        error!("{:?}", e);
+
+        if let Err(e) = log_error(&state.pg, e).await {
+            error!("{:?}", e);
+        }
+    }
 
-    if let Err(e) = run(&mut state).await {
-        println!("{:?}", e);
-        log_error(&state.pg, e).await.unwrap();
+    result
+}
+
+async fn execute_stage(command: Command, state: &mut State) -> Result<()> {
+    match command {
+        Command::Run { .. } => run(state).await,
+        Command::OpeningBalance => opening_balance::create_opening_balance_records(state).await,
+        Command::Bets { provider, .. } => {
+            if let Some(start_date) = state.options.start_date {
+                opening_balance::ensure_opening_balance_history(&state.pg, start_date).await?;
+            }
+
+            opening_balance::load_credit_players(state).await?;
+            archive_provider(provider, state).await
+        }
+        Command::DetailsSync => sync_bet_details(&state.maria_db).await,
+        Command::DetailsRetry { .. } => {
+            retry_pending_details(state).await?;
+            sync_bet_details(&state.maria_db).await
+        }
+        Command::ValidateConfig | Command::Status { .. } => Ok(()),
     }
 }
@@ -8,7 +8,7 @@ use crate::{
     consts::OPENING_BALANCE_TABLE_NAME,
     enums::PositionEnum,
     helpers::{
-        get_hong_kong_11_hours_from_date,
+        get_hong_kong_11_hours_from_date, metrics,
         query_helper::{get_archive_schema_name, get_dynamic_table_name},
     },
     types::UserID,
@@ -68,10 +68,12 @@ pub async fn get_player_chunk(pool: &PgPool, limit: i64, offset: i64) -> Result<
 }
 
 pub async fn insert_opening_balance_records(
-    pool: &PgPool,
+    pg_transaction: &mut Transaction<'_, Postgres>,
     records: Vec<OpeningBalance>,
     date: Date,
 ) -> Result<()> {
+    let _timer = metrics::db_query_timer("pg", "insert_opening_balance_records");
+
     let db_schema = get_archive_schema_name(date);
     let table_name = get_dynamic_table_name(OPENING_BALANCE_TABLE_NAME, date);
 
@@ -103,7 +105,7 @@ pub async fn insert_opening_balance_records(
             .take_arguments()
             .context("Failed to take arguments for insert opening balance")?,
     )
-    .execute(pool)
+    .execute(&mut **pg_transaction)
     .await
     .context("Failed to insert opening balance records")?;
 
@@ -153,7 +155,9 @@ pub async fn update_opening_balance_amount(
     table_name: String,
     date: Date,
     update_map: &FxHashMap<UserID, i64>,
-) -> Result<()> {
+) -> Result<u64> {
+    let _timer = metrics::db_query_timer("pg", "update_opening_balance_amount");
+
     let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new(format!(
         r#"
             UPDATE {schema}.{table_name} AS ob SET
@@ -178,7 +182,7 @@ pub async fn update_opening_balance_amount(
 
     let mut query = query_builder.build();
 
-    sqlx::query_with(
+    let result = sqlx::query_with(
         query.sql(),
         query
             .take_arguments()
@@ -188,5 +192,31 @@ pub async fn update_opening_balance_amount(
     .await
     .context("Failed to update opening amount")?;
 
-    Ok(())
+    Ok(result.rows_affected())
+}
+
+/// Rows which `update_opening_balance_amount` would update, for dry runs
+pub async fn count_opening_balance_rows(
+    pg_conn: &mut Transaction<'_, Postgres>,
+    schema: String,
+    table_name: String,
+    date: Date,
+    user_ids: Vec<UserID>,
+) -> Result<u64> {
+    let user_ids: Vec<Uuid> = user_ids.into_iter().map(|id| id.0).collect();
+
+    let count: i64 = sqlx::query_scalar(&format!(
+        r#"
+            SELECT COUNT(*)
+            FROM {schema}.{table_name}
+            WHERE user_id = ANY($1) AND creation_date >= $2
+        "#
+    ))
+    .bind(user_ids)
+    .bind(get_hong_kong_11_hours_from_date(date))
+    .fetch_one(&mut **pg_conn)
+    .await
+    .context("Failed to count opening balance records")?;
+
+    Ok(count as u64)
 }
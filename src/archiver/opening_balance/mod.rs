@@ -1,4 +1,4 @@
-use anyhow::{bail, Result};
+use anyhow::{bail, Context, Result};
 use sqlx::PgPool;
 use time::{Date, Duration, OffsetDateTime};
 use uuid::Uuid;
@@ -12,6 +12,7 @@ use crate::{
         query_helper::{get_archive_schema_name, get_dynamic_table_name},
         subtract_one_month, State,
     },
+    types::UserID,
 };
 
 use self::loader::{
@@ -24,27 +25,19 @@ pub async fn create_opening_balance_records(state: &mut State) -> Result<()> {
     let tomorrow = OffsetDateTime::now_utc().date() + Duration::days(1);
 
     if last_opening_balance_date >= tomorrow {
-        // Seems like procedure has already been executed today
-        return Ok(());
+        // Seems like procedure has already been executed today, but bets still need credit players
+        return load_credit_players(state).await;
     }
 
-    let limit: i64 = 2000;
-    let mut players_offset: i64 = 0;
-
-    loop {
-        let players_chunk = get_player_chunk(&state.pg, limit, players_offset).await?;
-
-        let mut user_ids = vec![];
-        let players_chunk_len = players_chunk.len();
-
-        for user in players_chunk {
-            user_ids.push(user.user_id.clone());
-
-            if user.is_credit {
-                state.add_credit_player(user.user_id);
-            }
-        }
+    // Records are upserted with 'amount + EXCLUDED.amount', so a partially created
+    // stage would be doubled on the next run. Either all records are created or none.
+    let mut pg_transaction = state
+        .pg
+        .begin()
+        .await
+        .context("Failed to start opening balance transaction")?;
 
+    for user_ids in load_player_chunks(state).await? {
         let opening_balance_records =
             get_opening_balance_records(&state.pg, last_opening_balance_date, user_ids).await?;
 
@@ -63,7 +56,7 @@ pub async fn create_opening_balance_records(state: &mut State) -> Result<()> {
                 .collect();
 
             insert_opening_balance_records(
-                &state.pg,
+                &mut pg_transaction,
                 opening_balance_records,
                 new_opening_balance_date,
             )
@@ -73,6 +66,41 @@ pub async fn create_opening_balance_records(state: &mut State) -> Result<()> {
                 break;
             }
         }
+    }
+
+    pg_transaction
+        .commit()
+        .await
+        .context("Failed to commit opening balance transaction")
+}
+
+/// Fills credit players cache without touching opening balance tables.
+/// Needed when bets are archived without running opening balance stage first.
+pub async fn load_credit_players(state: &mut State) -> Result<()> {
+    load_player_chunks(state).await?;
+    Ok(())
+}
+
+/// Returns IDs of all players in chunks and fills credit players cache on the way
+async fn load_player_chunks(state: &mut State) -> Result<Vec<Vec<UserID>>> {
+    let limit: i64 = 2000;
+    let mut players_offset: i64 = 0;
+    let mut chunks = vec![];
+
+    loop {
+        let players_chunk = get_player_chunk(&state.pg, limit, players_offset).await?;
+        let players_chunk_len = players_chunk.len();
+        let mut user_ids = vec![];
+
+        for user in players_chunk {
+            user_ids.push(user.user_id);
+
+            if user.is_credit {
+                state.add_credit_player(user.user_id);
+            }
+        }
+
+        chunks.push(user_ids);
 
         if players_chunk_len < limit as usize {
             break;
@@ -81,6 +109,26 @@ pub async fn create_opening_balance_records(state: &mut State) -> Result<()> {
         players_offset += limit;
     }
 
+    Ok(chunks)
+}
+
+/// WL of archived bets is only added to existing opening balance records,
+/// so a backfilled window can't start before opening balance history
+pub async fn ensure_opening_balance_history(pool: &PgPool, start_date: Date) -> Result<()> {
+    let figures_date = start_date + Duration::days(1);
+
+    let last_opening_balance_date = get_last_opening_balance_creation_date(
+        pool,
+        get_archive_schema_name(figures_date),
+        get_dynamic_table_name(OPENING_BALANCE_TABLE_NAME, figures_date),
+    )
+    .await
+    .with_context(|| format!("Opening balance table for {figures_date} is not available"))?;
+
+    if last_opening_balance_date.is_none() {
+        bail!("No opening balance records in the month of {figures_date}, can't archive from {start_date}");
+    }
+
     Ok(())
 }
 
@@ -0,0 +1,42 @@
+use time::{Date, Duration, OffsetDateTime};
+
+use super::{retention::RetentionPolicy, CHUNK_SIZE};
+
+pub const DEFAULT_CONCURRENCY: usize = 4;
+pub const DEFAULT_DETAILS_CONCURRENCY: usize = 16;
+pub const DEFAULT_DETAILS_MAX_AGE_DAYS: i64 = 7;
+
+#[derive(Debug, Clone)]
+pub struct RunOptions {
+    /// Bets with `last_status_change` from 11:00 HK of this date are archived.
+    /// Everything before `cutoff_date` is archived when not set.
+    pub start_date: Option<Date>,
+    /// Bets with `last_status_change` before 11:00 HK of this date are archived
+    pub cutoff_date: Date,
+    pub chunk_size: usize,
+    /// Compute figures without writing anything to PG or MariaDB
+    pub dry_run: bool,
+    /// Amount of providers archived at the same time
+    pub concurrency: usize,
+    /// Amount of detail requests sent to a provider at the same time
+    pub details_concurrency: usize,
+    /// Failed detail requests are retried until they are this old
+    pub details_max_age_days: i64,
+    /// Narrows `cutoff_date` per product, provider and bet status
+    pub retention: RetentionPolicy,
+}
+
+impl Default for RunOptions {
+    fn default() -> Self {
+        Self {
+            start_date: None,
+            cutoff_date: OffsetDateTime::now_utc().date() - Duration::days(1),
+            chunk_size: CHUNK_SIZE,
+            dry_run: false,
+            concurrency: DEFAULT_CONCURRENCY,
+            details_concurrency: DEFAULT_DETAILS_CONCURRENCY,
+            details_max_age_days: DEFAULT_DETAILS_MAX_AGE_DAYS,
+            retention: RetentionPolicy::default(),
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use std::{collections::BTreeMap, fs, str::FromStr};
+
+use anyhow::{bail, Context, Result};
+use rustc_hash::FxHashMap;
+use serde::Deserialize;
+use strum::VariantArray;
+use time::{Date, Duration, OffsetDateTime};
+
+use crate::enums::{
+    bet::BetStatus,
+    provider::{GameProvider, Product},
+};
+
+/// Bets with one of `statuses` settled before 11:00 HK of `cutoff_date` are archived
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatusCutoff {
+    pub statuses: Vec<String>,
+    pub cutoff_date: Date,
+}
+
+/// Policy file as written by people. Keys are validated in `RetentionPolicy::try_from`.
+///
+/// ```json
+/// {
+///     "default_days": 1,
+///     "excluded_statuses": ["ACTIVE", "PENDING"],
+///     "products": { "LOTTERY": 7 },
+///     "providers": { "parlay": 3 },
+///     "statuses": { "SUSPENDED": 30 }
+/// }
+/// ```
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawRetentionPolicy {
+    default_days: Option<i64>,
+    excluded_statuses: Option<Vec<String>>,
+    #[serde(default)]
+    products: FxHashMap<String, i64>,
+    #[serde(default)]
+    providers: FxHashMap<String, i64>,
+    #[serde(default)]
+    statuses: FxHashMap<String, i64>,
+}
+
+/// How many days settled bets stay in PG before they are archived.
+/// The most specific rule wins: status, then provider, then product, then default.
+#[derive(Debug, Clone)]
+pub struct RetentionPolicy {
+    default_days: i64,
+    excluded_statuses: Vec<BetStatus>,
+    products: FxHashMap<Product, i64>,
+    providers: FxHashMap<GameProvider, i64>,
+    statuses: FxHashMap<BetStatus, i64>,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            default_days: 1,
+            excluded_statuses: vec![BetStatus::Active, BetStatus::Pending],
+            products: FxHashMap::default(),
+            providers: FxHashMap::default(),
+            statuses: FxHashMap::default(),
+        }
+    }
+}
+
+impl RetentionPolicy {
+    pub fn load(path: &str) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read retention policy file '{path}'"))?;
+
+        let raw: RawRetentionPolicy = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse retention policy file '{path}'"))?;
+
+        raw.try_into()
+            .with_context(|| format!("Invalid retention policy in '{path}'"))
+    }
+
+    fn retention_days(&self, provider: GameProvider, status: BetStatus) -> i64 {
+        self.statuses
+            .get(&status)
+            .or_else(|| self.providers.get(&provider))
+            .or_else(|| self.products.get(&provider.get_product()))
+            .copied()
+            .unwrap_or(self.default_days)
+    }
+
+    /// Groups archivable statuses of the provider by their cutoff date.
+    /// Cutoff never goes past `max_cutoff_date` requested for the run.
+    pub fn status_cutoffs(
+        &self,
+        provider: GameProvider,
+        max_cutoff_date: Date,
+    ) -> Vec<StatusCutoff> {
+        let today = OffsetDateTime::now_utc().date();
+        let mut statuses_by_cutoff: BTreeMap<Date, Vec<String>> = BTreeMap::new();
+
+        for status in BetStatus::VARIANTS {
+            if self.excluded_statuses.contains(status) {
+                continue;
+            }
+
+            let cutoff_date = (today - Duration::days(self.retention_days(provider, *status)))
+                .min(max_cutoff_date);
+
+            statuses_by_cutoff
+                .entry(cutoff_date)
+                .or_default()
+                .push(status.to_string());
+        }
+
+        statuses_by_cutoff
+            .into_iter()
+            .map(|(cutoff_date, statuses)| StatusCutoff {
+                statuses,
+                cutoff_date,
+            })
+            .collect()
+    }
+}
+
+impl TryFrom<RawRetentionPolicy> for RetentionPolicy {
+    type Error = anyhow::Error;
+
+    fn try_from(raw: RawRetentionPolicy) -> Result<Self> {
+        let default = RetentionPolicy::default();
+
+        let excluded_statuses = match raw.excluded_statuses {
+            Some(statuses) => statuses
+                .iter()
+                .map(|status| parse_status(status))
+                .collect::<Result<_>>()?,
+            None => default.excluded_statuses,
+        };
+
+        let policy = RetentionPolicy {
+            default_days: raw.default_days.unwrap_or(default.default_days),
+            excluded_statuses,
+            products: parse_keys(raw.products, |key| {
+                Product::from_str(key).with_context(|| format!("Unknown product: '{key}'"))
+            })?,
+            providers: parse_keys(raw.providers, GameProvider::from_str)?,
+            statuses: parse_keys(raw.statuses, parse_status)?,
+        };
+
+        let all_days = [policy.default_days]
+            .into_iter()
+            .chain(policy.products.values().copied())
+            .chain(policy.providers.values().copied())
+            .chain(policy.statuses.values().copied());
+
+        for days in all_days {
+            // Figures of the current day are not final yet
+            if days < 1 {
+                bail!("Retention must be at least 1 day, got {days}");
+            }
+        }
+
+        Ok(policy)
+    }
+}
+
+fn parse_status(value: &str) -> Result<BetStatus> {
+    BetStatus::from_str(value).with_context(|| format!("Unknown bet status: '{value}'"))
+}
+
+fn parse_keys<K, F>(days_by_name: FxHashMap<String, i64>, parse: F) -> Result<FxHashMap<K, i64>>
+where
+    K: Eq + std::hash::Hash,
+    F: Fn(&str) -> Result<K>,
+{
+    days_by_name
+        .into_iter()
+        .map(|(name, days)| Ok((parse(&name)?, days)))
+        .collect()
+}
@@ -0,0 +1,269 @@
+use std::{fmt::Write, time::Duration};
+
+use anyhow::{Context, Result};
+use rustc_hash::FxHashMap;
+use sqlx::{FromRow, PgPool, Postgres, QueryBuilder};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::enums::provider::GameProvider;
+
+#[derive(Debug, Default, Clone)]
+pub struct ProviderStats {
+    pub bets_archived: u64,
+    pub details_fetched: u64,
+    pub details_failed: u64,
+    /// Detail requests not sent because the circuit breaker of the connector was open
+    pub details_skipped: u64,
+    pub circuit_opened: u64,
+    pub debts_upserted: u64,
+    pub opening_balance_rows_updated: u64,
+    /// Time spent on chunks of the provider, including detail requests
+    pub duration: Duration,
+}
+
+/// Collected while archiving and saved to 'archive_run_provider_summary' at the end
+#[derive(Debug, Default)]
+pub struct RunStats {
+    by_provider: FxHashMap<GameProvider, ProviderStats>,
+}
+
+impl RunStats {
+    pub fn provider(&mut self, provider: GameProvider) -> &mut ProviderStats {
+        self.by_provider.entry(provider).or_default()
+    }
+}
+
+pub async fn start_run_summary(pg: &PgPool, command: &str) -> Result<Uuid> {
+    mark_interrupted_runs(pg).await?;
+
+    sqlx::query_scalar(
+        r#"
+            INSERT INTO public.archive_run_summary (command, outcome, started_at)
+            VALUES ($1, $2, $3)
+            RETURNING id
+        "#,
+    )
+    .bind(command)
+    .bind("running")
+    .bind(OffsetDateTime::now_utc())
+    .fetch_one(pg)
+    .await
+    .context("Failed to create archive run summary")
+}
+
+/// Runs are started by cron or a daemon at most once a day, so a run still 'running'
+/// a day after its start was killed before its summary was saved
+const INTERRUPTED_AFTER: time::Duration = time::Duration::days(1);
+
+/// Younger running summaries may belong to another process which is still working
+async fn mark_interrupted_runs(pg: &PgPool) -> Result<()> {
+    sqlx::query(
+        r#"
+            UPDATE public.archive_run_summary
+            SET outcome = $1, error = $2
+            WHERE outcome = $3
+                AND started_at < $4
+        "#,
+    )
+    .bind("interrupted")
+    .bind("Run stopped before its summary was saved")
+    .bind("running")
+    .bind(OffsetDateTime::now_utc() - INTERRUPTED_AFTER)
+    .execute(pg)
+    .await
+    .context("Failed to mark interrupted archive run summaries")?;
+
+    Ok(())
+}
+
+pub async fn finish_run_summary(
+    pg: &PgPool,
+    summary_id: Uuid,
+    archive_run_id: Option<Uuid>,
+    result: &Result<()>,
+    stats: RunStats,
+) -> Result<()> {
+    let (outcome, error) = match result {
+        Ok(_) => ("succeeded", None),
+        Err(e) => ("failed", Some(format!("{:?}", e))),
+    };
+
+    let mut transaction = pg
+        .begin()
+        .await
+        .context("Failed to start run summary transaction")?;
+
+    sqlx::query(
+        r#"
+            UPDATE public.archive_run_summary
+            SET outcome = $1, error = $2, finished_at = $3, archive_run_id = $4
+            WHERE id = $5
+        "#,
+    )
+    .bind(outcome)
+    .bind(error)
+    .bind(OffsetDateTime::now_utc())
+    .bind(archive_run_id)
+    .bind(summary_id)
+    .execute(&mut *transaction)
+    .await
+    .context("Failed to finish archive run summary")?;
+
+    if !stats.by_provider.is_empty() {
+        let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new(
+            r#"
+                INSERT INTO public.archive_run_provider_summary (
+                    run_summary_id,
+                    provider,
+                    bets_archived,
+                    details_fetched,
+                    details_failed,
+                    details_skipped,
+                    circuit_opened,
+                    debts_upserted,
+                    opening_balance_rows_updated,
+                    duration_ms
+                )
+            "#,
+        );
+
+        query_builder.push_values(stats.by_provider, |mut b, (provider, stats)| {
+            b.push_bind(summary_id)
+                .push_bind(provider.to_string())
+                .push_bind(stats.bets_archived as i64)
+                .push_bind(stats.details_fetched as i64)
+                .push_bind(stats.details_failed as i64)
+                .push_bind(stats.details_skipped as i64)
+                .push_bind(stats.circuit_opened as i64)
+                .push_bind(stats.debts_upserted as i64)
+                .push_bind(stats.opening_balance_rows_updated as i64)
+                .push_bind(stats.duration.as_millis() as i64);
+        });
+
+        query_builder
+            .build()
+            .execute(&mut *transaction)
+            .await
+            .context("Failed to save provider summaries")?;
+    }
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit run summary transaction")
+}
+
+#[derive(FromRow)]
+struct RunSummaryRow {
+    id: Uuid,
+    command: String,
+    outcome: String,
+    error: Option<String>,
+    started_at: OffsetDateTime,
+    finished_at: Option<OffsetDateTime>,
+}
+
+#[derive(FromRow)]
+struct ProviderSummaryRow {
+    run_summary_id: Uuid,
+    provider: String,
+    bets_archived: i64,
+    details_fetched: i64,
+    details_failed: i64,
+    details_skipped: i64,
+    circuit_opened: i64,
+    debts_upserted: i64,
+    opening_balance_rows_updated: i64,
+    duration_ms: i64,
+}
+
+pub async fn print_recent_runs(pg: &PgPool, limit: i64) -> Result<()> {
+    print!("{}", format_recent_runs(pg, limit).await?);
+
+    Ok(())
+}
+
+/// Output of the 'status' command
+pub async fn format_recent_runs(pg: &PgPool, limit: i64) -> Result<String> {
+    let runs: Vec<RunSummaryRow> = sqlx::query_as(
+        r#"
+            SELECT
+                id,
+                command,
+                outcome,
+                error,
+                started_at,
+                finished_at
+            FROM public.archive_run_summary
+            ORDER BY started_at DESC
+            LIMIT $1
+        "#,
+    )
+    .bind(limit)
+    .fetch_all(pg)
+    .await
+    .context("Failed to fetch recent archive runs")?;
+
+    let run_ids: Vec<Uuid> = runs.iter().map(|run| run.id).collect();
+
+    let providers: Vec<ProviderSummaryRow> = sqlx::query_as(
+        r#"
+            SELECT
+                run_summary_id,
+                provider,
+                bets_archived,
+                details_fetched,
+                details_failed,
+                details_skipped,
+                circuit_opened,
+                debts_upserted,
+                opening_balance_rows_updated,
+                duration_ms
+            FROM public.archive_run_provider_summary
+            WHERE run_summary_id = ANY($1)
+            ORDER BY provider
+        "#,
+    )
+    .bind(run_ids)
+    .fetch_all(pg)
+    .await
+    .context("Failed to fetch provider summaries")?;
+
+    let mut output = String::new();
+
+    for run in runs {
+        let finished_at = run
+            .finished_at
+            .map(|date| date.to_string())
+            .unwrap_or("-".to_string());
+
+        writeln!(
+            output,
+            "{} {}: {}, started {}, finished {}",
+            run.id, run.command, run.outcome, run.started_at, finished_at
+        )?;
+
+        if let Some(error) = run.error {
+            writeln!(output, "  error: {error}")?;
+        }
+
+        for provider in providers.iter().filter(|p| p.run_summary_id == run.id) {
+            writeln!(
+                output,
+                "  {}: {} bets, {} details fetched, {} failed, {} skipped, circuit opened {} times, {} debts, {} opening balances, {} ms",
+                provider.provider,
+                provider.bets_archived,
+                provider.details_fetched,
+                provider.details_failed,
+                provider.details_skipped,
+                provider.circuit_opened,
+                provider.debts_upserted,
+                provider.opening_balance_rows_updated,
+                provider.duration_ms
+            )?;
+        }
+    }
+
+    Ok(output)
+}
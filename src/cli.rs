@@ -0,0 +1,245 @@
+use std::str::FromStr;
+
+use anyhow::bail;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use strum_macros::AsRefStr;
+use time::{macros::format_description, Date};
+
+use crate::{
+    archiver::{
+        options::{
+            RunOptions, DEFAULT_CONCURRENCY, DEFAULT_DETAILS_CONCURRENCY,
+            DEFAULT_DETAILS_MAX_AGE_DAYS,
+        },
+        retention::RetentionPolicy,
+        CHUNK_SIZE,
+    },
+    enums::provider::GameProvider,
+};
+
+#[derive(Parser, Debug)]
+#[command(about = "Moves settled bets from PostgreSQL into the MariaDB archive")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command,
+
+    /// Format of run progress and errors written to stderr
+    #[arg(long, value_enum, global = true, default_value_t = LogFormat::Text)]
+    pub log_format: LogFormat,
+
+    /// Write Prometheus metrics to this file when the command finishes (textfile collector)
+    #[arg(long, global = true)]
+    pub metrics_file: Option<String>,
+
+    /// Serve Prometheus metrics on this address while the process is running, e.g. 127.0.0.1:9184.
+    /// Combine with `--interval-minutes` to keep it up between runs
+    #[arg(long, global = true)]
+    pub metrics_addr: Option<String>,
+
+    /// Keep running and repeat the command every this many minutes (daemon mode)
+    #[arg(long, global = true, value_parser = parse_interval_minutes)]
+    pub interval_minutes: Option<u64>,
+
+    /// Fail on any missing or invalid provider config instead of disabling that provider's details
+    #[arg(long, global = true)]
+    pub strict_config: bool,
+}
+
+#[derive(Subcommand, Debug, Clone, AsRefStr)]
+#[strum(serialize_all = "kebab-case")]
+pub enum Command {
+    /// Run the whole pipeline: opening balances, all providers and details sync
+    Run {
+        #[command(flatten)]
+        archive: ArchiveArgs,
+
+        /// Amount of providers archived in parallel
+        #[arg(long, default_value_t = DEFAULT_CONCURRENCY, value_parser = parse_concurrency)]
+        concurrency: usize,
+    },
+
+    /// Create missing opening balance records up to tomorrow
+    OpeningBalance,
+
+    /// Archive bets of a single provider
+    Bets {
+        /// Provider name as stored in DB, e.g. 'pragmatic_slot'
+        #[arg(long, value_parser = parse_provider)]
+        provider: GameProvider,
+
+        #[command(flatten)]
+        archive: ArchiveArgs,
+    },
+
+    /// Copy fetched details and replays into the MariaDB bet table
+    DetailsSync,
+
+    /// Fetch details which failed during archiving and copy them into the MariaDB bet table
+    DetailsRetry {
+        /// Give up on details of bets archived more than this many days ago
+        #[arg(long, default_value_t = DEFAULT_DETAILS_MAX_AGE_DAYS, value_parser = parse_max_age_days)]
+        max_age_days: i64,
+
+        /// Amount of detail requests sent in parallel
+        #[arg(long, default_value_t = DEFAULT_DETAILS_CONCURRENCY, value_parser = parse_concurrency)]
+        details_concurrency: usize,
+    },
+
+    /// Load provider configs and check that every connector can be built, always in strict mode
+    ValidateConfig,
+
+    /// Show recent runs with per-provider statistics
+    Status {
+        /// Amount of runs to show
+        #[arg(long, default_value_t = 10)]
+        limit: i64,
+    },
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ArchiveArgs {
+    /// Archive bets settled from 11:00 HK of this date (YYYY-MM-DD). Defaults to all older bets
+    #[arg(long, value_parser = parse_date)]
+    pub from: Option<Date>,
+
+    /// Archive bets settled before 11:00 HK of this date (YYYY-MM-DD). Defaults to yesterday
+    #[arg(long, visible_alias = "to", value_parser = parse_date)]
+    pub cutoff_date: Option<Date>,
+
+    /// Amount of bets loaded from PG per chunk
+    #[arg(long, default_value_t = CHUNK_SIZE, value_parser = parse_chunk_size)]
+    pub chunk_size: usize,
+
+    /// Print bet counts, WL deltas and debts instead of archiving
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// JSON file with retention days per product, provider and bet status
+    #[arg(long)]
+    pub retention_policy: Option<String>,
+
+    /// Amount of detail requests sent to a provider in parallel
+    #[arg(long, default_value_t = DEFAULT_DETAILS_CONCURRENCY, value_parser = parse_concurrency)]
+    pub details_concurrency: usize,
+}
+
+impl Command {
+    /// Amount of providers archived in parallel by this command
+    pub fn concurrency(&self) -> usize {
+        match self {
+            Command::Run { concurrency, .. } => *concurrency,
+            _ => 1,
+        }
+    }
+
+    /// Options of commands which archive bets
+    pub fn run_options(&self) -> anyhow::Result<Option<RunOptions>> {
+        let options = match self {
+            Command::Run {
+                archive,
+                concurrency,
+            } => RunOptions {
+                concurrency: *concurrency,
+                ..archive.clone().try_into()?
+            },
+            Command::Bets { archive, .. } => archive.clone().try_into()?,
+            Command::DetailsRetry {
+                max_age_days,
+                details_concurrency,
+            } => RunOptions {
+                details_max_age_days: *max_age_days,
+                details_concurrency: *details_concurrency,
+                ..RunOptions::default()
+            },
+            _ => return Ok(None),
+        };
+
+        Ok(Some(options))
+    }
+}
+
+impl TryFrom<ArchiveArgs> for RunOptions {
+    type Error = anyhow::Error;
+
+    fn try_from(args: ArchiveArgs) -> Result<Self, Self::Error> {
+        let default = RunOptions::default();
+        let cutoff_date = args.cutoff_date.unwrap_or(default.cutoff_date);
+
+        // Figures of the current day are not final yet
+        if cutoff_date > default.cutoff_date {
+            bail!(
+                "Cutoff date {cutoff_date} is after {}, only closed days can be archived",
+                default.cutoff_date
+            );
+        }
+
+        if let Some(from) = args.from {
+            if from >= cutoff_date {
+                bail!("'--from' {from} must be before cutoff date {cutoff_date}");
+            }
+        }
+
+        let retention = match args.retention_policy {
+            Some(path) => RetentionPolicy::load(&path)?,
+            None => default.retention.clone(),
+        };
+
+        Ok(RunOptions {
+            start_date: args.from,
+            retention,
+            cutoff_date,
+            chunk_size: args.chunk_size,
+            dry_run: args.dry_run,
+            details_concurrency: args.details_concurrency,
+            ..default
+        })
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum LogFormat {
+    Text,
+    Json,
+}
+
+fn parse_provider(value: &str) -> Result<GameProvider, String> {
+    GameProvider::from_str(value).map_err(|e| e.to_string())
+}
+
+fn parse_date(value: &str) -> Result<Date, String> {
+    Date::parse(value, format_description!("[year]-[month]-[day]"))
+        .map_err(|e| format!("Expected date in YYYY-MM-DD format: {e}"))
+}
+
+fn parse_chunk_size(value: &str) -> Result<usize, String> {
+    let chunk_size: usize = value
+        .parse()
+        .map_err(|_| format!("'{value}' is not a number"))?;
+
+    if chunk_size == 0 || chunk_size > CHUNK_SIZE {
+        return Err(format!("Chunk size must be between 1 and {CHUNK_SIZE}"));
+    }
+
+    Ok(chunk_size)
+}
+
+fn parse_concurrency(value: &str) -> Result<usize, String> {
+    match value.parse() {
+        Ok(0) | Err(_) => Err(format!("'{value}' is not a positive number")),
+        Ok(concurrency) => Ok(concurrency),
+    }
+}
+
+fn parse_max_age_days(value: &str) -> Result<i64, String> {
+    match value.parse() {
+        Ok(days) if days > 0 => Ok(days),
+        _ => Err(format!("'{value}' is not a positive number of days")),
+    }
+}
+
+fn parse_interval_minutes(value: &str) -> Result<u64, String> {
+    match value.parse() {
+        Ok(0) | Err(_) => Err(format!("'{value}' is not a positive number of minutes")),
+        Ok(minutes) => Ok(minutes),
+    }
+}
@@ -1,13 +1,24 @@
 use std::net::Ipv4Addr;
 
 use anyhow::{Context, Result};
+use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
-use crate::types::{ProviderBetID, Url, Username};
+use crate::{
+    archiver::bets::loader::{BetDetails, BetDetailsRequest},
+    connectors::{
+        http::{HttpClient, HttpConfig},
+        BetDetailsConnector,
+    },
+    types::{ProviderBetID, Url, Username},
+};
 
 impl Connector {
-    pub fn new(config: Config) -> Self {
-        Self { config }
+    pub fn new(config: Config) -> Result<Self> {
+        Ok(Self {
+            http: HttpClient::new(config.http.clone())?,
+            config,
+        })
     }
 
     pub async fn get_transaction_history_result(
@@ -23,13 +34,13 @@ impl Connector {
             platform_tx_id: provider_bet_id.clone(),
         };
 
-        let result: AeTransactionHistoryResponse = reqwest::Client::new()
-            .post(&format!(
-                "{}/getTransactionHistoryResult",
-                &self.config.host
-            ))
-            .form(&payload)
-            .send()
+        let result: AeTransactionHistoryResponse = self
+            .http
+            .send(|client| {
+                Ok(client
+                    .post(format!("{}/getTransactionHistoryResult", &self.config.host))
+                    .form(&payload))
+            })
             .await
             .with_context(|| {
                 format!(
@@ -53,6 +64,21 @@ impl Connector {
     }
 }
 
+#[async_trait]
+impl BetDetailsConnector for Connector {
+    async fn fetch_details(&self, bet: &BetDetailsRequest) -> Result<Option<BetDetails>> {
+        let url = self
+            .get_transaction_history_result(&bet.username, &bet.provider_bet_id)
+            .await?;
+
+        Ok(Some(BetDetails {
+            id: bet.id,
+            details: None,
+            replay: Some(url),
+        }))
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Config {
@@ -62,11 +88,14 @@ pub struct Config {
     pub agent_id: String,
     pub secret_key: String,
     pub ip_list: Vec<Ipv4Addr>,
+    #[serde(default)]
+    pub http: HttpConfig,
 }
 
 #[derive(Debug)]
 pub struct Connector {
     config: Config,
+    http: HttpClient,
 }
 
 #[derive(Serialize)]
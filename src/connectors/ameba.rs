@@ -1,14 +1,22 @@
 use std::net::Ipv4Addr;
 
 use anyhow::{bail, Context, Result};
-use reqwest::Client;
+use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
-use crate::types::{ProviderBetID, Url, Username};
+use crate::{
+    archiver::bets::loader::{BetDetails, BetDetailsRequest},
+    connectors::{
+        http::{HttpClient, HttpConfig},
+        BetDetailsConnector,
+    },
+    types::{ProviderBetID, Url, Username},
+};
 
 #[derive(Debug)]
 pub struct Connector {
     config: AmebaConfig,
+    http: HttpClient,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -19,11 +27,16 @@ pub struct AmebaConfig {
     #[serde(rename = "siteID")]
     pub site_id: i64,
     pub ip_list: Vec<Ipv4Addr>,
+    #[serde(default)]
+    pub http: HttpConfig,
 }
 
 impl Connector {
-    pub fn new(config: AmebaConfig) -> Self {
-        Self { config }
+    pub fn new(config: AmebaConfig) -> Result<Self> {
+        Ok(Self {
+            http: HttpClient::new(config.http.clone())?,
+            config,
+        })
     }
 
     pub async fn get_round_history(
@@ -38,10 +51,13 @@ impl Connector {
             account_name: username.clone(),
         };
 
-        let result: GetRoundHistoryResponse = Client::new()
-            .post(format!("{}/dms/api", &self.config.api_url))
-            .form(&payload)
-            .send()
+        let result: GetRoundHistoryResponse = self
+            .http
+            .send(|client| {
+                Ok(client
+                    .post(format!("{}/dms/api", &self.config.api_url))
+                    .form(&payload))
+            })
             .await
             .with_context(|| format!("Failed to fetch bet detail for '{}'", bet_id))?
             .json()
@@ -55,6 +71,17 @@ impl Connector {
     }
 }
 
+#[async_trait]
+impl BetDetailsConnector for Connector {
+    async fn fetch_details(&self, bet: &BetDetailsRequest) -> Result<Option<BetDetails>> {
+        let url = self
+            .get_round_history(&bet.username, &bet.provider_bet_id)
+            .await?;
+
+        Ok(Some(BetDetails::with_result(bet.id, url)))
+    }
+}
+
 #[derive(Serialize)]
 struct GetRoundHistoryPayload {
     action: &'static str,
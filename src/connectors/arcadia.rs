@@ -1,14 +1,22 @@
 use std::net::Ipv4Addr;
 
 use anyhow::{bail, Context, Result};
-use reqwest::Client;
+use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
-use crate::types::{ProviderBetID, Url};
+use crate::{
+    archiver::bets::loader::{BetDetails, BetDetailsRequest},
+    connectors::{
+        http::{HttpClient, HttpConfig},
+        BetDetailsConnector,
+    },
+    types::{ProviderBetID, Url},
+};
 
 #[derive(Debug)]
 pub struct Connector {
     config: ArcadiaConfig,
+    http: HttpClient,
 }
 
 #[derive(Deserialize, Debug, Serialize)]
@@ -17,11 +25,16 @@ pub struct ArcadiaConfig {
     pub api_url: Url,
     pub authentication: String,
     pub ip_list: Vec<Ipv4Addr>,
+    #[serde(default)]
+    pub http: HttpConfig,
 }
 
 impl Connector {
-    pub fn new(config: ArcadiaConfig) -> Self {
-        Self { config }
+    pub fn new(config: ArcadiaConfig) -> Result<Self> {
+        Ok(Self {
+            http: HttpClient::new(config.http.clone())?,
+            config,
+        })
     }
 
     pub async fn get_bet_history(&self, bet_id: &ProviderBetID) -> Result<Url> {
@@ -30,10 +43,13 @@ impl Connector {
             al_trans_id: bet_id.clone(),
         };
 
-        let result: Response = Client::new()
-            .post(format!("{}/GetGameResult", &self.config.api_url))
-            .json(&payload)
-            .send()
+        let result: Response = self
+            .http
+            .send(|client| {
+                Ok(client
+                    .post(format!("{}/GetGameResult", &self.config.api_url))
+                    .json(&payload))
+            })
             .await
             .with_context(|| {
                 format!(
@@ -61,6 +77,14 @@ impl Connector {
     }
 }
 
+#[async_trait]
+impl BetDetailsConnector for Connector {
+    async fn fetch_details(&self, bet: &BetDetailsRequest) -> Result<Option<BetDetails>> {
+        let url = self.get_bet_history(&bet.provider_bet_id).await?;
+        Ok(Some(BetDetails::with_result(bet.id, url)))
+    }
+}
+
 #[derive(Serialize)]
 struct BetHistoryPayload {
     #[serde(rename = "ALTransID")]
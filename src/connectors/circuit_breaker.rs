@@ -0,0 +1,165 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+use strum_macros::Display;
+use thiserror::Error;
+
+/// Optional "circuitBreaker" key of a 'provider_config' row
+///
+/// ```json
+/// "circuitBreaker": { "failureThreshold": 5, "coolDownSecs": 60 }
+/// ```
+#[derive(Deserialize, Serialize, Debug, Clone, Copy)]
+#[serde(rename_all = "camelCase", default, deny_unknown_fields)]
+pub struct CircuitBreakerConfig {
+    /// Consecutive failed calls which open the circuit
+    pub failure_threshold: u32,
+    /// How long calls are skipped before a single probe call is let through
+    pub cool_down_secs: u64,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            cool_down_secs: 60,
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+#[error("Circuit breaker of '{0}' connector is open, call skipped")]
+pub struct CircuitOpen(pub String);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+#[strum(serialize_all = "kebab-case")]
+pub enum Transition {
+    Opened,
+    HalfOpened,
+    Closed,
+}
+
+#[derive(Debug)]
+enum Circuit {
+    Closed {
+        failures: u32,
+    },
+    Open {
+        since: Instant,
+    },
+    /// Only the probe call is in flight, everything else is skipped
+    HalfOpen,
+}
+
+/// Stops calling a provider which keeps failing
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    name: String,
+    config: CircuitBreakerConfig,
+    circuit: Mutex<Circuit>,
+}
+
+impl CircuitBreaker {
+    pub fn new(name: String, config: CircuitBreakerConfig) -> Self {
+        Self {
+            name,
+            config,
+            circuit: Mutex::new(Circuit::Closed { failures: 0 }),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Fails while the circuit is open. After the cool-down one probe call is let through.
+    pub fn acquire(&self) -> Result<Permit<'_>, CircuitOpen> {
+        let mut circuit = self.circuit.lock().unwrap();
+
+        let transition = match *circuit {
+            Circuit::Closed { .. } => None,
+            Circuit::Open { since }
+                if since.elapsed() >= Duration::from_secs(self.config.cool_down_secs) =>
+            {
+                *circuit = Circuit::HalfOpen;
+                Some(Transition::HalfOpened)
+            }
+            Circuit::Open { .. } | Circuit::HalfOpen => return Err(CircuitOpen(self.name.clone())),
+        };
+
+        Ok(Permit {
+            breaker: self,
+            transition,
+            recorded: false,
+        })
+    }
+
+    fn record(&self, succeeded: bool) -> Option<Transition> {
+        let mut circuit = self.circuit.lock().unwrap();
+
+        match (&*circuit, succeeded) {
+            (Circuit::HalfOpen, true) => {
+                *circuit = Circuit::Closed { failures: 0 };
+                Some(Transition::Closed)
+            }
+            (Circuit::HalfOpen, false) => {
+                *circuit = Circuit::Open {
+                    since: Instant::now(),
+                };
+                Some(Transition::Opened)
+            }
+            (Circuit::Closed { .. }, true) => {
+                *circuit = Circuit::Closed { failures: 0 };
+                None
+            }
+            (Circuit::Closed { failures }, false) => {
+                let failures = failures + 1;
+
+                if failures >= self.config.failure_threshold {
+                    *circuit = Circuit::Open {
+                        since: Instant::now(),
+                    };
+                    return Some(Transition::Opened);
+                }
+
+                *circuit = Circuit::Closed { failures };
+                None
+            }
+            // Calls started before the circuit opened
+            (Circuit::Open { .. }, _) => None,
+        }
+    }
+}
+
+/// Allowed call. Its outcome is recorded by `record`, a permit dropped before that counts as a failure,
+/// so a probe which was cancelled or lost on an error never leaves the circuit half-open.
+#[must_use]
+#[derive(Debug)]
+pub struct Permit<'a> {
+    breaker: &'a CircuitBreaker,
+    transition: Option<Transition>,
+    recorded: bool,
+}
+
+impl Permit<'_> {
+    /// Set when this call is the half-open probe
+    pub fn transition(&self) -> Option<Transition> {
+        self.transition
+    }
+
+    pub fn record(mut self, succeeded: bool) -> Option<Transition> {
+        self.recorded = true;
+        self.breaker.record(succeeded)
+    }
+}
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        if !self.recorded {
+            self.breaker.record(false);
+        }
+    }
+}
@@ -1,13 +1,17 @@
 use std::net::Ipv4Addr;
 
 use anyhow::{anyhow, bail, Context, Result};
-use reqwest::Client;
+use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use serde_repr::{Deserialize_repr, Serialize_repr};
 
 use crate::{
-    archiver::bets::loader::Bet,
+    archiver::bets::loader::{BetDetails, BetDetailsRequest},
+    connectors::{
+        http::{HttpClient, HttpConfig},
+        BetDetailsConnector,
+    },
     helpers::crypto,
     types::{Currency, ProviderBetID, Url, Username},
 };
@@ -15,14 +19,18 @@ use crate::{
 #[derive(Debug)]
 pub struct Connector {
     config: DotConnectionsConfig,
+    http: HttpClient,
 }
 
 impl Connector {
-    pub fn new(config: DotConnectionsConfig) -> Self {
-        Self { config }
+    pub fn new(config: DotConnectionsConfig) -> Result<Self> {
+        Ok(Self {
+            http: HttpClient::new(config.http.clone())?,
+            config,
+        })
     }
 
-    pub async fn get_bet_history(&self, bet: &Bet) -> Result<Url> {
+    pub async fn get_bet_history(&self, bet: &BetDetailsRequest) -> Result<Url> {
         let transaction: Value =
             serde_json::from_str(bet.transactions.get(0).ok_or_else(|| {
                 anyhow!(
@@ -53,10 +61,13 @@ impl Connector {
             brand_uid: bet.username.clone(),
         };
 
-        let response: HistoryResponse<HistoryData> = Client::new()
-            .post(format!("{}/dcs/getReplay", &self.config.api_url))
-            .json(&payload)
-            .send()
+        let response: HistoryResponse<HistoryData> = self
+            .http
+            .send(|client| {
+                Ok(client
+                    .post(format!("{}/dcs/getReplay", &self.config.api_url))
+                    .json(&payload))
+            })
             .await
             .with_context(|| {
                 format!(
@@ -92,6 +103,14 @@ impl Connector {
     }
 }
 
+#[async_trait]
+impl BetDetailsConnector for Connector {
+    async fn fetch_details(&self, bet: &BetDetailsRequest) -> Result<Option<BetDetails>> {
+        let url = self.get_bet_history(bet).await?;
+        Ok(Some(BetDetails::with_result(bet.id, url)))
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct GetHistoryPayload {
     brand_id: String,
@@ -137,4 +156,6 @@ pub struct DotConnectionsConfig {
     pub brand_id: String,
     pub api_key: String,
     pub ip_list: Vec<Ipv4Addr>,
+    #[serde(default)]
+    pub http: HttpConfig,
 }
@@ -0,0 +1,107 @@
+use std::net::Ipv4Addr;
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    archiver::bets::loader::{BetDetails, BetDetailsRequest},
+    connectors::{
+        http::{HttpClient, HttpConfig},
+        BetDetailsConnector,
+    },
+    types::{ProviderBetID, Url},
+};
+
+#[derive(Debug)]
+pub struct Connector {
+    config: HabaneroConfig,
+    http: HttpClient,
+}
+
+#[derive(Deserialize, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HabaneroConfig {
+    pub api_url: Url,
+    pub brand_id: Uuid,
+    pub api_key: Uuid,
+    pub ip_list: Vec<Ipv4Addr>,
+    #[serde(default)]
+    pub http: HttpConfig,
+}
+
+impl Connector {
+    pub fn new(config: HabaneroConfig) -> Result<Self> {
+        Ok(Self {
+            http: HttpClient::new(config.http.clone())?,
+            config,
+        })
+    }
+
+    pub async fn get_game_details(&self, bet_id: &ProviderBetID) -> Result<Url> {
+        let payload = GameDetailsPayload {
+            brand_id: self.config.brand_id,
+            api_key: self.config.api_key,
+            game_instance_id: bet_id,
+        };
+
+        let result: Response = self
+            .http
+            .send(|client| {
+                Ok(client
+                    .post(format!("{}/jsonapi/GetGameDetails", &self.config.api_url))
+                    .json(&payload))
+            })
+            .await
+            .with_context(|| {
+                format!(
+                    "Failed to send request for game details to Habanero API for bet: '{}'",
+                    bet_id
+                )
+            })?
+            .json()
+            .await
+            .with_context(|| {
+                format!(
+                    "Failed to parse response from Habanero game details for bet: {}",
+                    bet_id
+                )
+            })?;
+
+        match result.game_details_url {
+            Some(url) if result.success => Ok(url),
+            _ => bail!(
+                "Got error from Habanero API for game details of '{}': {}",
+                bet_id,
+                result.message
+            ),
+        }
+    }
+}
+
+#[async_trait]
+impl BetDetailsConnector for Connector {
+    async fn fetch_details(&self, bet: &BetDetailsRequest) -> Result<Option<BetDetails>> {
+        let url = self.get_game_details(&bet.provider_bet_id).await?;
+        Ok(Some(BetDetails::with_result(bet.id, url)))
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct GameDetailsPayload<'a> {
+    brand_id: Uuid,
+    #[serde(rename = "APIKey")]
+    api_key: Uuid,
+    game_instance_id: &'a ProviderBetID,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+struct Response {
+    success: bool,
+    #[serde(default)]
+    message: String,
+    game_details_url: Option<Url>,
+}
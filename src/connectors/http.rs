@@ -0,0 +1,123 @@
+use std::{sync::Arc, time::Duration};
+
+use anyhow::{Context, Result};
+use rand::Rng;
+use reqwest::{Client, RequestBuilder, Response};
+use serde::{Deserialize, Serialize};
+
+use super::rate_limit::{RateLimitConfig, RateLimiter};
+
+/// Optional "http" key of a 'provider_config' row
+///
+/// ```json
+/// "http": {
+///     "connectTimeoutMs": 3000,
+///     "readTimeoutMs": 20000,
+///     "maxRetries": 2,
+///     "rateLimit": { "requestsPerSecond": 5, "burst": 10 }
+/// }
+/// ```
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", default, deny_unknown_fields)]
+pub struct HttpConfig {
+    pub connect_timeout_ms: u64,
+    /// Whole request including the response body
+    pub read_timeout_ms: u64,
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled with every next one
+    pub retry_base_delay_ms: u64,
+    /// Requests are not limited when not set
+    pub rate_limit: Option<RateLimitConfig>,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            connect_timeout_ms: 5_000,
+            read_timeout_ms: 30_000,
+            max_retries: 3,
+            retry_base_delay_ms: 500,
+            rate_limit: None,
+        }
+    }
+}
+
+/// Client of a single connector. Connections to the provider are pooled between requests.
+#[derive(Debug, Clone)]
+pub struct HttpClient {
+    client: Client,
+    config: HttpConfig,
+    rate_limiter: Option<Arc<RateLimiter>>,
+}
+
+impl HttpClient {
+    pub fn new(config: HttpConfig) -> Result<Self> {
+        let client = Client::builder()
+            .connect_timeout(Duration::from_millis(config.connect_timeout_ms))
+            .timeout(Duration::from_millis(config.read_timeout_ms))
+            .build()
+            .context("Failed to build HTTP client")?;
+
+        let rate_limiter = config
+            .rate_limit
+            .map(RateLimiter::new)
+            .transpose()?
+            .map(Arc::new);
+
+        Ok(Self {
+            client,
+            config,
+            rate_limiter,
+        })
+    }
+
+    /// Sends the request made by `build` and retries it on timeouts, connection errors and 5xx responses.
+    /// The request is built again for every attempt, so signatures and timestamps stay fresh.
+    /// Every attempt waits for the rate limiter, retries count against the provider quota too.
+    pub async fn send<F>(&self, build: F) -> Result<Response>
+    where
+        F: Fn(&Client) -> Result<RequestBuilder>,
+    {
+        let mut attempt = 0;
+
+        loop {
+            if let Some(rate_limiter) = &self.rate_limiter {
+                rate_limiter.acquire().await;
+            }
+
+            let result = build(&self.client)?.send().await;
+
+            let retryable = match &result {
+                Ok(response) => response.status().is_server_error(),
+                // Other errors, e.g. an invalid URL or body, fail the same way on every attempt
+                Err(e) => e.is_timeout() || e.is_connect(),
+            };
+
+            if !retryable || attempt >= self.config.max_retries {
+                let attempts = attempt + 1;
+
+                return match result {
+                    Ok(response) if retryable => response.error_for_status().with_context(|| {
+                        format!("Provider kept failing after {attempts} attempts")
+                    }),
+                    Ok(response) => Ok(response),
+                    Err(e) => Err(e)
+                        .with_context(|| format!("HTTP request failed after {attempts} attempts")),
+                };
+            }
+
+            tokio::time::sleep(self.retry_delay(attempt)).await;
+            attempt += 1;
+        }
+    }
+
+    /// Full jitter, so parallel detail requests do not retry at the same moment
+    fn retry_delay(&self, attempt: u32) -> Duration {
+        let max_delay = self
+            .config
+            .retry_base_delay_ms
+            .saturating_mul(1 << attempt.min(16));
+
+        Duration::from_millis(rand::thread_rng().gen_range(0..=max_delay))
+    }
+}
@@ -0,0 +1,117 @@
+use std::net::Ipv4Addr;
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::{
+    archiver::bets::loader::{BetDetails, BetDetailsRequest},
+    connectors::{
+        http::{HttpClient, HttpConfig},
+        BetDetailsConnector,
+    },
+    helpers::crypto,
+    types::{ProviderBetID, Url},
+};
+
+#[derive(Debug)]
+pub struct Connector {
+    config: JiliConfig,
+    http: HttpClient,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct JiliConfig {
+    pub api_url: Url,
+    pub agent_id: String,
+    pub agent_key: String,
+    pub ip_list: Vec<Ipv4Addr>,
+    #[serde(default)]
+    pub http: HttpConfig,
+}
+
+impl Connector {
+    pub fn new(config: JiliConfig) -> Result<Self> {
+        Ok(Self {
+            http: HttpClient::new(config.http.clone())?,
+            config,
+        })
+    }
+
+    pub async fn get_round_detail_url(&self, bet_id: &ProviderBetID) -> Result<Url> {
+        let query = serde_urlencoded::to_string(RoundDetailQuery {
+            wagers_id: bet_id,
+            agent_id: &self.config.agent_id,
+        })
+        .context("Failed to serialize Jili round detail query")?;
+
+        let response: Response = self
+            .http
+            .send(|client| {
+                // Key depends on the date, so it is signed again for every attempt
+                let key = crypto::jili_key(
+                    &query,
+                    OffsetDateTime::now_utc(),
+                    &self.config.agent_id,
+                    &self.config.agent_key,
+                )
+                .context("Failed to sign Jili request")?;
+
+                // Jili signs the exact query string, so it is sent as is
+                Ok(client.post(format!(
+                    "{}/GetGameDetailUrl?{query}&Key={key}",
+                    &self.config.api_url
+                )))
+            })
+            .await
+            .with_context(|| format!("Failed to fetch Jili round detail for '{}'", bet_id))?
+            .json()
+            .await
+            .with_context(|| format!("Failed to parse Jili round detail for '{}'", bet_id))?;
+
+        match (response.error_code, response.data) {
+            (0, Some(data)) => Ok(data.url),
+            _ => bail!(
+                "Jili round detail API error {}: {}",
+                response.error_code,
+                response.message
+            ),
+        }
+    }
+}
+
+#[async_trait]
+impl BetDetailsConnector for Connector {
+    async fn fetch_details(&self, bet: &BetDetailsRequest) -> Result<Option<BetDetails>> {
+        let url = self.get_round_detail_url(&bet.provider_bet_id).await?;
+
+        Ok(Some(BetDetails {
+            id: bet.id,
+            details: None,
+            replay: Some(url),
+        }))
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct RoundDetailQuery<'a> {
+    wagers_id: &'a ProviderBetID,
+    agent_id: &'a str,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+struct Response {
+    error_code: i64,
+    message: String,
+    data: Option<RoundDetailData>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+struct RoundDetailData {
+    url: Url,
+}
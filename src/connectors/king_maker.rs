@@ -1,14 +1,22 @@
 use std::net::Ipv4Addr;
 
 use anyhow::{bail, Context, Result};
-use reqwest::Client;
+use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
-use crate::types::{ProviderBetID, Url, Username};
+use crate::{
+    archiver::bets::loader::{BetDetails, BetDetailsRequest},
+    connectors::{
+        http::{HttpClient, HttpConfig},
+        BetDetailsConnector,
+    },
+    types::{ProviderBetID, Url, Username},
+};
 
 #[derive(Debug)]
 pub struct Connector {
     config: KingMakerConfig,
+    http: HttpClient,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -21,11 +29,16 @@ pub struct KingMakerConfig {
     pub client_id: String,
     pub client_secret: String,
     pub ip_list: Vec<Ipv4Addr>,
+    #[serde(default)]
+    pub http: HttpConfig,
 }
 
 impl Connector {
-    pub fn new(config: KingMakerConfig) -> Self {
-        Self { config }
+    pub fn new(config: KingMakerConfig) -> Result<Self> {
+        Ok(Self {
+            http: HttpClient::new(config.http.clone())?,
+            config,
+        })
     }
 
     pub async fn get_round_history(
@@ -33,12 +46,14 @@ impl Connector {
         username: &Username,
         round_id: &ProviderBetID,
     ) -> Result<SuccessHistoryResponse> {
-        let result: Response<SuccessHistoryResponse> = Client::new()
-            .get(format!(
-                "{}/history/providers/{}/rounds/{round_id}/users/{username}",
-                self.config.api_url, self.config.game_provider_code
-            ))
-            .send()
+        let result: Response<SuccessHistoryResponse> = self
+            .http
+            .send(|client| {
+                Ok(client.get(format!(
+                    "{}/history/providers/{}/rounds/{round_id}/users/{username}",
+                    self.config.api_url, self.config.game_provider_code
+                )))
+            })
             .await
             .with_context(|| {
                 format!(
@@ -63,6 +78,17 @@ impl Connector {
     }
 }
 
+#[async_trait]
+impl BetDetailsConnector for Connector {
+    async fn fetch_details(&self, bet: &BetDetailsRequest) -> Result<Option<BetDetails>> {
+        let history = self
+            .get_round_history(&bet.username, &bet.provider_bet_id)
+            .await?;
+
+        Ok(Some(BetDetails::with_result(bet.id, history)))
+    }
+}
+
 #[derive(Deserialize, Debug)]
 #[serde(untagged)]
 enum Response<T> {
@@ -1,123 +1,228 @@
-use std::str::FromStr;
+use std::{fmt::Debug, str::FromStr, sync::Arc};
 
-use anyhow::{anyhow, Context, Result};
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
 use rustc_hash::FxHashMap;
+use serde::{de::DeserializeOwned, Deserialize};
 use sqlx::PgPool;
+use thiserror::Error;
 
-use crate::enums::provider::{
-    GameProvider, LiveCasinoProvider, OnlineCasinoProvider, SlotProvider,
+use crate::{
+    archiver::bets::loader::{BetDetails, BetDetailsRequest},
+    enums::provider::{
+        GameProvider, LiveCasinoProvider, OnlineCasinoProvider, ProviderGameKind, SlotProvider,
+    },
+    helpers::logger::log_warning,
+    types::ProviderGameVendorID,
 };
 
-use self::royal_slot_gaming::RoyalSlotGamingGameConfig;
+use self::{
+    circuit_breaker::{CircuitBreaker, CircuitBreakerConfig},
+    royal_slot_gaming::RoyalSlotGamingGameConfig,
+};
 
 pub mod ae;
 pub mod ameba;
 pub mod arcadia;
+pub mod circuit_breaker;
 pub mod dot_connections;
+pub mod habanero;
+pub mod http;
+pub mod jili;
 pub mod king_maker;
+pub mod pg_soft;
 pub mod pragmatic;
+pub mod rate_limit;
 pub mod royal_slot_gaming;
+pub mod sa;
+pub mod wm;
+
+/// Fetches provider-side details of an archived bet
+#[async_trait]
+pub trait BetDetailsConnector: Debug + Send + Sync {
+    /// `None` when the provider has nothing to add to the bet
+    async fn fetch_details(&self, bet: &BetDetailsRequest) -> Result<Option<BetDetails>>;
+}
 
 #[derive(Debug)]
+pub struct RegisteredConnector {
+    pub connector: Arc<dyn BetDetailsConnector>,
+    pub circuit_breaker: CircuitBreaker,
+}
+
+/// Connector of every provider which has bet details. One connector may serve several providers.
+#[derive(Debug, Default)]
 pub struct Connectors {
-    pub ae: ae::Connector,
-    pub ameba: ameba::Connector,
-    pub arcadia: arcadia::Connector,
-    pub king_maker: king_maker::Connector,
-    pub pragmatic: pragmatic::Connector,
-    pub royal_slot_gaming: royal_slot_gaming::Connector,
-    pub dot_connections: dot_connections::Connector,
+    by_provider: FxHashMap<GameProvider, RegisteredConnector>,
+}
+
+/// Provider has a connector, but its config was missing or invalid when connectors were loaded
+#[derive(Error, Debug)]
+#[error("Details of '{0}' are disabled, connector is not loaded")]
+pub struct ConnectorDisabled(pub GameProvider);
+
+impl Connectors {
+    pub fn get(&self, provider: GameProvider) -> Option<&RegisteredConnector> {
+        self.by_provider.get(&provider)
+    }
+
+    pub fn is_disabled(&self, provider: GameProvider) -> bool {
+        CONNECTOR_PROVIDERS.contains(&provider) && !self.by_provider.contains_key(&provider)
+    }
+
+    fn register(&mut self, provider: GameProvider, connector: RegisteredConnector) {
+        self.by_provider.insert(provider, connector);
+    }
+}
+
+/// Settings shared by every 'provider_config' row, next to the connector's own keys
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct CommonConfig {
+    #[serde(default)]
+    circuit_breaker: CircuitBreakerConfig,
 }
 
-pub async fn load_connectors(pg_pool: &PgPool) -> Result<Connectors> {
+/// Providers which have a connector. Every provider is built from its own 'provider_config' row,
+/// even when it shares the implementation with another one.
+const CONNECTOR_PROVIDERS: [GameProvider; 19] = [
+    GameProvider::LiveCasino(LiveCasinoProvider::Sexy),
+    GameProvider::LiveCasino(LiveCasinoProvider::SA),
+    GameProvider::Slot(SlotProvider::Ameba),
+    GameProvider::OnlineCasino(OnlineCasinoProvider::Arcadia),
+    GameProvider::OnlineCasino(OnlineCasinoProvider::Kingmaker),
+    GameProvider::LiveCasino(LiveCasinoProvider::Pragmatic),
+    GameProvider::Slot(SlotProvider::Pragmatic),
+    GameProvider::Slot(SlotProvider::RoyalSlotGaming),
+    GameProvider::Slot(SlotProvider::Relax),
+    GameProvider::Slot(SlotProvider::YGG),
+    GameProvider::Slot(SlotProvider::Hacksaw),
+    GameProvider::LiveCasino(LiveCasinoProvider::WM),
+    GameProvider::Slot(SlotProvider::WM),
+    GameProvider::Slot(SlotProvider::PG),
+    GameProvider::OnlineCasino(OnlineCasinoProvider::PG),
+    GameProvider::Slot(SlotProvider::Jili),
+    GameProvider::OnlineCasino(OnlineCasinoProvider::Jili),
+    GameProvider::Slot(SlotProvider::Habanero),
+    GameProvider::OnlineCasino(OnlineCasinoProvider::Habanero),
+];
+
+/// A missing or invalid config disables details of that provider only, with a warning in 'system_log'.
+/// In `strict` mode it fails the whole load instead.
+pub async fn load_connectors(pg_pool: &PgPool, strict: bool) -> Result<Connectors> {
     let configs = get_provider_configs(pg_pool).await?;
+    let mut connectors = Connectors::default();
 
-    let mut sexy_config: Option<ae::Config> = None;
-    let mut ameba_config: Option<ameba::AmebaConfig> = None;
-    let mut arcadia_config: Option<arcadia::ArcadiaConfig> = None;
-    let mut king_maker_config: Option<king_maker::KingMakerConfig> = None;
-    let mut pragmatic_config: Option<pragmatic::PragmaticConfig> = None;
-    let mut royal_slot_config: Option<royal_slot_gaming::RoyalSlotGamingConfig> = None;
-    let mut dot_connections_config: Option<dot_connections::DotConnectionsConfig> = None;
-
-    for config in configs {
-        match config.game_provider {
-            GameProvider::LiveCasino(LiveCasinoProvider::Sexy) => {
-                sexy_config = Some(
-                    serde_json::from_str(&config.config).context("Failed to parse Sexy config")?,
-                );
-            }
-            GameProvider::Slot(SlotProvider::Ameba) => {
-                ameba_config = Some(
-                    serde_json::from_str(&config.config).context("Failed to parse Ameba config")?,
-                );
-            }
-            GameProvider::OnlineCasino(OnlineCasinoProvider::Arcadia) => {
-                arcadia_config = Some(
-                    serde_json::from_str(&config.config)
-                        .context("Failed to parse Arcadia config")?,
-                );
-            }
-            GameProvider::OnlineCasino(OnlineCasinoProvider::Kingmaker) => {
-                king_maker_config = Some(
-                    serde_json::from_str(&config.config)
-                        .context("Failed to parse Kingmaker config")?,
-                );
-            }
-            GameProvider::LiveCasino(LiveCasinoProvider::Pragmatic) => {
-                pragmatic_config = Some(
-                    serde_json::from_str(&config.config)
-                        .context("Failed to parse Pragmatic config")?,
-                );
-            }
-            GameProvider::Slot(SlotProvider::RoyalSlotGaming) => {
-                royal_slot_config = Some(
-                    serde_json::from_str(&config.config)
-                        .context("Failed to parse RoyalSlotGaming config")?,
-                );
-            }
-            GameProvider::Slot(SlotProvider::Relax) => {
-                dot_connections_config = Some(
-                    serde_json::from_str(&config.config)
-                        .context("Failed to parse DotConnections config")?,
-                );
-            }
-            _ => {
-                return Err(anyhow!(
-                    "Loaded invalid provider config: '{}'",
-                    config.game_provider.as_ref()
-                ))
+    for provider in CONNECTOR_PROVIDERS {
+        match load_connector(pg_pool, provider, &configs).await {
+            Ok(connector) => connectors.register(provider, connector),
+            Err(e) if strict => return Err(e),
+            Err(e) => {
+                log_warning(
+                    pg_pool,
+                    format!(
+                        "Details of '{}' are disabled, connector is not loaded: {:#}",
+                        provider.as_ref(),
+                        e
+                    ),
+                )
+                .await?
             }
         }
     }
 
-    let royal_slot_gaming_configs = load_royal_slot_game_configs(pg_pool).await?;
-    let mut games_by_vendor_id = FxHashMap::default();
+    Ok(connectors)
+}
+
+async fn load_connector(
+    pg_pool: &PgPool,
+    provider: GameProvider,
+    configs: &[ProviderConfig],
+) -> Result<RegisteredConnector> {
+    let config = configs
+        .iter()
+        .find(|c| c.game_provider == provider)
+        .with_context(|| format!("{} config not found", provider.as_ref()))?;
 
-    for item in royal_slot_gaming_configs {
-        games_by_vendor_id.insert(item.game_id.clone(), item);
-    }
+    let common: CommonConfig = parse_config(config)?;
 
-    Ok(Connectors {
-        dot_connections: dot_connections::Connector::new(
-            dot_connections_config.context("dot_connections config not found")?,
-        ),
-        ae: ae::Connector::new(sexy_config.context("sexy config not found")?),
-        ameba: ameba::Connector::new(ameba_config.context("ameba config not found")?),
-        arcadia: arcadia::Connector::new(arcadia_config.context("arcadia config not found")?),
-        king_maker: king_maker::Connector::new(
-            king_maker_config.context("king_maker config not found")?,
-        ),
-        pragmatic: pragmatic::Connector::new(
-            pragmatic_config.context("pragmatic config not found")?,
-        ),
-        royal_slot_gaming: royal_slot_gaming::Connector::new(
-            royal_slot_config.context("royal_slot_gaming config not found")?,
-            games_by_vendor_id,
-        ),
+    Ok(RegisteredConnector {
+        connector: build_connector(pg_pool, provider, config).await?,
+        circuit_breaker: CircuitBreaker::new(provider.as_ref().to_string(), common.circuit_breaker),
     })
 }
 
+async fn build_connector(
+    pg_pool: &PgPool,
+    provider: GameProvider,
+    config: &ProviderConfig,
+) -> Result<Arc<dyn BetDetailsConnector>> {
+    let connector: Arc<dyn BetDetailsConnector> = match provider {
+        GameProvider::LiveCasino(LiveCasinoProvider::Sexy) => {
+            Arc::new(ae::Connector::new(parse_config(config)?)?)
+        }
+        GameProvider::LiveCasino(LiveCasinoProvider::SA) => {
+            Arc::new(sa::Connector::new(parse_config(config)?)?)
+        }
+        GameProvider::Slot(SlotProvider::Ameba) => {
+            Arc::new(ameba::Connector::new(parse_config(config)?)?)
+        }
+        GameProvider::OnlineCasino(OnlineCasinoProvider::Arcadia) => {
+            Arc::new(arcadia::Connector::new(parse_config(config)?)?)
+        }
+        GameProvider::OnlineCasino(OnlineCasinoProvider::Kingmaker) => {
+            Arc::new(king_maker::Connector::new(parse_config(config)?)?)
+        }
+        GameProvider::LiveCasino(LiveCasinoProvider::Pragmatic)
+        | GameProvider::Slot(SlotProvider::Pragmatic) => {
+            Arc::new(pragmatic::Connector::new(parse_config(config)?)?)
+        }
+        GameProvider::Slot(SlotProvider::RoyalSlotGaming) => {
+            let mut games_by_vendor_id = FxHashMap::default();
+
+            for item in load_royal_slot_game_configs(pg_pool).await? {
+                games_by_vendor_id.insert(item.game_id.clone(), item);
+            }
+
+            Arc::new(royal_slot_gaming::Connector::new(
+                parse_config(config)?,
+                games_by_vendor_id,
+            )?)
+        }
+        GameProvider::Slot(SlotProvider::Relax)
+        | GameProvider::Slot(SlotProvider::YGG)
+        | GameProvider::Slot(SlotProvider::Hacksaw) => {
+            Arc::new(dot_connections::Connector::new(parse_config(config)?)?)
+        }
+        GameProvider::LiveCasino(LiveCasinoProvider::WM) | GameProvider::Slot(SlotProvider::WM) => {
+            Arc::new(wm::Connector::new(
+                parse_config(config)?,
+                load_game_kinds(pg_pool, provider).await?,
+            )?)
+        }
+        GameProvider::Slot(SlotProvider::PG)
+        | GameProvider::OnlineCasino(OnlineCasinoProvider::PG) => {
+            Arc::new(pg_soft::Connector::new(parse_config(config)?)?)
+        }
+        GameProvider::Slot(SlotProvider::Jili)
+        | GameProvider::OnlineCasino(OnlineCasinoProvider::Jili) => {
+            Arc::new(jili::Connector::new(parse_config(config)?)?)
+        }
+        GameProvider::Slot(SlotProvider::Habanero)
+        | GameProvider::OnlineCasino(OnlineCasinoProvider::Habanero) => {
+            Arc::new(habanero::Connector::new(parse_config(config)?)?)
+        }
+        _ => bail!("Provider '{}' has no connector", provider.as_ref()),
+    };
+
+    Ok(connector)
+}
+
+fn parse_config<T: DeserializeOwned>(config: &ProviderConfig) -> Result<T> {
+    serde_json::from_str(&config.config)
+        .with_context(|| format!("Failed to parse {} config", config.game_provider.as_ref()))
+}
+
 struct ProviderConfig {
     game_provider: GameProvider,
     config: String,
@@ -136,16 +241,12 @@ async fn get_provider_configs(pg_pool: &PgPool) -> Result<Vec<ProviderConfig>> {
                 game_provider,
                 config
             FROM public.provider_config
-            WHERE game_provider IN (
-                'sexy',
-                'ameba',
-                'arcadia',
-                'kingmaker',
-                'pragmatic_live_casino',
-                'royal_slot_gaming',
-                'relax'
-            )
-        "#
+            WHERE game_provider = ANY($1)
+        "#,
+        &CONNECTOR_PROVIDERS
+            .iter()
+            .map(|p| p.as_ref().to_string())
+            .collect::<Vec<String>>()
     )
     .fetch_all(pg_pool)
     .await
@@ -188,3 +289,39 @@ async fn load_royal_slot_game_configs(pg_pool: &PgPool) -> Result<Vec<RoyalSlotG
 
     Ok(result)
 }
+
+/// WM has a history endpoint per game kind
+async fn load_game_kinds(
+    pg_pool: &PgPool,
+    provider: GameProvider,
+) -> Result<FxHashMap<ProviderGameVendorID, ProviderGameKind>> {
+    let db_data = sqlx::query!(
+        r#"
+            SELECT
+                vendor_id,
+                kind
+            FROM public.provider_game
+            WHERE provider = $1
+        "#,
+        provider.as_ref()
+    )
+    .fetch_all(pg_pool)
+    .await
+    .with_context(|| format!("Failed to load {} games", provider.as_ref()))?;
+
+    let mut result = FxHashMap::default();
+
+    for item in db_data {
+        let Some(vendor_id) = item.vendor_id else {
+            continue;
+        };
+
+        let kind = ProviderGameKind::from_str(&item.kind).with_context(|| {
+            format!("Unknown kind '{}' of {} game", item.kind, provider.as_ref())
+        })?;
+
+        result.insert(ProviderGameVendorID(vendor_id), kind);
+    }
+
+    Ok(result)
+}
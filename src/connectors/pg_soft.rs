@@ -0,0 +1,196 @@
+use std::{
+    net::Ipv4Addr,
+    time::{Duration, Instant},
+};
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::{
+    archiver::bets::loader::{BetDetails, BetDetailsRequest},
+    connectors::{
+        http::{HttpClient, HttpConfig},
+        BetDetailsConnector,
+    },
+    types::{ProviderBetID, ProviderGameVendorID, Url},
+};
+
+#[derive(Debug)]
+pub struct Connector {
+    config: PgSoftConfig,
+    http: HttpClient,
+    session: Mutex<Option<OperatorSession>>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PgSoftConfig {
+    pub api_url: Url,
+    /// Public page which redirects to the round history of a game
+    pub history_url: Url,
+    pub operator_token: String,
+    pub secret_key: String,
+    pub ip_list: Vec<Ipv4Addr>,
+    /// How long an operator session from LoginProxy is reused
+    #[serde(default = "default_session_ttl_secs")]
+    pub session_ttl_secs: u64,
+    #[serde(default)]
+    pub http: HttpConfig,
+}
+
+fn default_session_ttl_secs() -> u64 {
+    10 * 60
+}
+
+#[derive(Debug)]
+struct OperatorSession {
+    token: String,
+    expires_at: Instant,
+}
+
+impl Connector {
+    pub fn new(config: PgSoftConfig) -> Result<Self> {
+        Ok(Self {
+            http: HttpClient::new(config.http.clone())?,
+            config,
+            session: Mutex::new(None),
+        })
+    }
+
+    /// Redirect page of a round, valid only while the operator session is
+    fn get_round_history_url(
+        &self,
+        bet_id: &ProviderBetID,
+        game_vendor_id: &ProviderGameVendorID,
+        operator_session: &str,
+    ) -> Result<String> {
+        let query = serde_urlencoded::to_string(HistoryRedirectQuery {
+            psid: bet_id,
+            sid: bet_id,
+            gid: game_vendor_id,
+            lang: "en",
+            kind: "operator",
+            trace_id: Uuid::new_v4(),
+            t: operator_session,
+        })
+        .context("Failed to serialize PG Soft history redirect query")?;
+
+        Ok(format!(
+            "{}/history/redirect.html?{query}",
+            &self.config.history_url
+        ))
+    }
+
+    /// Session is shared by every redirect resolved until it expires
+    async fn operator_session(&self) -> Result<String> {
+        let mut session = self.session.lock().await;
+
+        if let Some(session) = &*session {
+            if session.expires_at > Instant::now() {
+                return Ok(session.token.clone());
+            }
+        }
+
+        let token = self.login_proxy().await?;
+
+        *session = Some(OperatorSession {
+            token: token.clone(),
+            expires_at: Instant::now() + Duration::from_secs(self.config.session_ttl_secs),
+        });
+
+        Ok(token)
+    }
+
+    async fn login_proxy(&self) -> Result<String> {
+        let payload = LoginProxyPayload {
+            operator_token: &self.config.operator_token,
+            secret_key: &self.config.secret_key,
+        };
+
+        let response: LoginProxyResponse = self
+            .http
+            .send(|client| {
+                Ok(client
+                    .post(format!(
+                        "{}/external/v1/Login/LoginProxy",
+                        &self.config.api_url
+                    ))
+                    .query(&[("trace_id", Uuid::new_v4())])
+                    .form(&payload))
+            })
+            .await
+            .context("Failed to request PG Soft operator session")?
+            .json()
+            .await
+            .context("Failed to parse PG Soft operator session response")?;
+
+        match (response.data, response.error) {
+            (Some(data), None) => Ok(data.operator_session),
+            (_, error) => bail!(
+                "PG Soft login proxy returned error: {}",
+                error.unwrap_or_default()
+            ),
+        }
+    }
+}
+
+#[async_trait]
+impl BetDetailsConnector for Connector {
+    /// The redirect is resolved while archiving, so the stored link doesn't depend on the session
+    async fn fetch_details(&self, bet: &BetDetailsRequest) -> Result<Option<BetDetails>> {
+        let operator_session = self.operator_session().await?;
+        let url = self.get_round_history_url(
+            &bet.provider_bet_id,
+            &bet.provider_game_vendor_id,
+            &operator_session,
+        )?;
+
+        let response = self
+            .http
+            .send(|client| Ok(client.get(&url)))
+            .await
+            .context("Failed to request PG Soft round history")?
+            .error_for_status()
+            .context("PG Soft round history returned error status")?;
+
+        Ok(Some(BetDetails {
+            id: bet.id,
+            details: None,
+            replay: Some(Url(response.url().to_string())),
+        }))
+    }
+}
+
+#[derive(Serialize)]
+struct LoginProxyPayload<'a> {
+    operator_token: &'a str,
+    secret_key: &'a str,
+}
+
+#[derive(Deserialize, Debug)]
+struct LoginProxyResponse {
+    data: Option<LoginProxyData>,
+    error: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize, Debug)]
+struct LoginProxyData {
+    operator_session: String,
+}
+
+#[derive(Serialize)]
+struct HistoryRedirectQuery<'a> {
+    /// Parent bet, the same as the bet for a single spin
+    psid: &'a ProviderBetID,
+    sid: &'a ProviderBetID,
+    gid: &'a ProviderGameVendorID,
+    lang: &'static str,
+    #[serde(rename = "type")]
+    kind: &'static str,
+    trace_id: Uuid,
+    /// Operator session
+    t: &'a str,
+}
@@ -1,11 +1,16 @@
 use std::net::Ipv4Addr;
 
 use anyhow::{Context, Result};
+use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use serde_repr::Deserialize_repr;
 
 use crate::{
-    archiver::bets::loader::Bet,
+    archiver::bets::loader::{BetDetails, BetDetailsRequest},
+    connectors::{
+        http::{HttpClient, HttpConfig},
+        BetDetailsConnector,
+    },
     enums::Language,
     helpers::crypto,
     types::{ProviderBetID, ProviderGameVendorID, Url, UserID},
@@ -23,19 +28,25 @@ pub struct PragmaticConfig {
     pub secure_login: String,
     pub ip_list: Vec<Ipv4Addr>,
     pub game_server_domain: Url,
+    #[serde(default)]
+    pub http: HttpConfig,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Debug)]
 pub struct Connector {
     config: PragmaticConfig,
+    http: HttpClient,
 }
 
 impl Connector {
-    pub fn new(config: PragmaticConfig) -> Self {
-        Self { config }
+    pub fn new(config: PragmaticConfig) -> Result<Self> {
+        Ok(Self {
+            http: HttpClient::new(config.http.clone())?,
+            config,
+        })
     }
 
-    pub async fn get_bet_round_history(&self, bet: &Bet) -> Result<Url> {
+    pub async fn get_bet_round_history(&self, bet: &BetDetailsRequest) -> Result<Url> {
         let mut payload = BetRoundHistoryPayload {
             game_id: bet.provider_game_vendor_id.clone(),
             language: Language::English,
@@ -51,10 +62,13 @@ impl Connector {
             self.config.secret_key
         )));
 
-        let response: BetRoundHistoryResponse = reqwest::Client::new()
-            .post(format!("{}/OpenHistoryExtended", self.config.api_url))
-            .form(&payload)
-            .send()
+        let response: BetRoundHistoryResponse = self
+            .http
+            .send(|client| {
+                Ok(client
+                    .post(format!("{}/OpenHistoryExtended", self.config.api_url))
+                    .form(&payload))
+            })
             .await
             .with_context(|| format!("Failed to fetch a bet info for bet: '{}", &bet.id))?
             .json()
@@ -71,6 +85,19 @@ impl Connector {
     }
 }
 
+#[async_trait]
+impl BetDetailsConnector for Connector {
+    async fn fetch_details(&self, bet: &BetDetailsRequest) -> Result<Option<BetDetails>> {
+        // Details of some bets are already saved by the game server
+        if bet.details.is_some() {
+            return Ok(None);
+        }
+
+        let url = self.get_bet_round_history(bet).await?;
+        Ok(Some(BetDetails::with_result(bet.id, url)))
+    }
+}
+
 #[derive(Serialize)]
 #[serde(rename = "camelCase")]
 struct BetRoundHistoryPayload {
@@ -0,0 +1,74 @@
+use std::time::Duration;
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use tokio::{sync::Mutex, time::Instant};
+
+/// "rateLimit" key of the provider "http" config
+///
+/// ```json
+/// "rateLimit": { "requestsPerSecond": 5, "burst": 10 }
+/// ```
+#[derive(Deserialize, Serialize, Debug, Clone, Copy)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct RateLimitConfig {
+    pub requests_per_second: f64,
+    /// Requests sent at once after a quiet period
+    pub burst: u32,
+}
+
+/// Token bucket shared by every request of a connector
+#[derive(Debug)]
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    bucket: Mutex<Bucket>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Result<Self> {
+        if config.requests_per_second <= 0.0 || config.burst == 0 {
+            bail!(
+                "Rate limit must allow at least one request, got {:?}",
+                config
+            );
+        }
+
+        Ok(Self {
+            config,
+            bucket: Mutex::new(Bucket {
+                tokens: config.burst as f64,
+                refilled_at: Instant::now(),
+            }),
+        })
+    }
+
+    /// Waits until a request is allowed. The lock is held while waiting,
+    /// so waiting requests are let through in the order they came.
+    pub async fn acquire(&self) {
+        let mut bucket = self.bucket.lock().await;
+        self.refill(&mut bucket);
+
+        if bucket.tokens < 1.0 {
+            let wait = (1.0 - bucket.tokens) / self.config.requests_per_second;
+            tokio::time::sleep(Duration::from_secs_f64(wait)).await;
+            self.refill(&mut bucket);
+        }
+
+        bucket.tokens -= 1.0;
+    }
+
+    fn refill(&self, bucket: &mut Bucket) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.refilled_at).as_secs_f64();
+
+        bucket.tokens = (bucket.tokens + elapsed * self.config.requests_per_second)
+            .min(self.config.burst as f64);
+        bucket.refilled_at = now;
+    }
+}
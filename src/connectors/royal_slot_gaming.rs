@@ -1,13 +1,18 @@
 use std::net::Ipv4Addr;
 
 use anyhow::{bail, Context, Result};
-use reqwest::{header::HeaderMap, Client};
+use async_trait::async_trait;
+use reqwest::header::HeaderMap;
 use rustc_hash::FxHashMap;
 use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
 
 use crate::{
-    archiver::bets::loader::Bet,
+    archiver::bets::loader::{BetDetails, BetDetailsRequest},
+    connectors::{
+        http::{HttpClient, HttpConfig},
+        BetDetailsConnector,
+    },
     enums::{provider::ProviderGameKind, Language},
     helpers::crypto,
     types::{
@@ -28,6 +33,8 @@ pub struct RoyalSlotGamingConfig {
     #[serde(rename = "desIV")]
     pub des_iv: String,
     pub ip_list: Vec<Ipv4Addr>,
+    #[serde(default)]
+    pub http: HttpConfig,
 }
 
 #[derive(Deserialize, Debug, Serialize)]
@@ -43,6 +50,7 @@ pub struct RoyalSlotGamingGameConfig {
 #[derive(Debug)]
 pub struct Connector {
     config: RoyalSlotGamingConfig,
+    http: HttpClient,
     games_by_vendor_id: FxHashMap<ProviderGameVendorID, RoyalSlotGamingGameConfig>,
 }
 
@@ -50,14 +58,19 @@ impl Connector {
     pub fn new(
         config: RoyalSlotGamingConfig,
         games_by_vendor_id: FxHashMap<ProviderGameVendorID, RoyalSlotGamingGameConfig>,
-    ) -> Self {
-        Self {
+    ) -> Result<Self> {
+        Ok(Self {
+            http: HttpClient::new(config.http.clone())?,
             config,
             games_by_vendor_id,
-        }
+        })
     }
 
-    pub async fn get_game_round_history(&self, bet: &Bet, lang: Option<Language>) -> Result<Url> {
+    pub async fn get_game_round_history(
+        &self,
+        bet: &BetDetailsRequest,
+        lang: Option<Language>,
+    ) -> Result<Url> {
         let game = self.games_by_vendor_id.get(&bet.provider_game_vendor_id);
 
         let game_type: u8 = game.map_or(1, |g| {
@@ -93,14 +106,17 @@ impl Connector {
             crypto::des_cbc_encrypt(&json_payload, &self.config.des_key, &self.config.des_iv)
                 .context("Failed to DES-CBC encrypt payload for RoyalSlotGaming")?;
 
-        let encrypted_response: String = Client::new()
-            .post(format!(
-                "{}/Player/GetGameMinDetailURLTokenBySeq",
-                &self.config.api_url
-            ))
-            .headers(self.generate_headers(&hash)?)
-            .body(format!("Msg={hash}"))
-            .send()
+        let encrypted_response: String = self
+            .http
+            .send(|client| {
+                Ok(client
+                    .post(format!(
+                        "{}/Player/GetGameMinDetailURLTokenBySeq",
+                        &self.config.api_url
+                    ))
+                    .headers(self.generate_headers(&hash)?)
+                    .body(format!("Msg={hash}")))
+            })
             .await
             .with_context(|| {
                 format!(
@@ -179,6 +195,14 @@ impl Connector {
     }
 }
 
+#[async_trait]
+impl BetDetailsConnector for Connector {
+    async fn fetch_details(&self, bet: &BetDetailsRequest) -> Result<Option<BetDetails>> {
+        let url = self.get_game_round_history(bet, None).await?;
+        Ok(Some(BetDetails::with_result(bet.id, url)))
+    }
+}
+
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "PascalCase")]
 struct RoundHistoryPayload {
@@ -0,0 +1,141 @@
+use std::net::Ipv4Addr;
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use time::{macros::format_description, macros::offset, OffsetDateTime};
+
+use crate::{
+    archiver::bets::loader::{BetDetails, BetDetailsRequest},
+    connectors::{
+        http::{HttpClient, HttpConfig},
+        BetDetailsConnector,
+    },
+    helpers::crypto,
+    types::{ProviderBetID, Url, Username},
+};
+
+#[derive(Debug)]
+pub struct Connector {
+    config: SaConfig,
+    http: HttpClient,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SaConfig {
+    pub api_url: Url,
+    pub secret_key: String,
+    pub md5_key: String,
+    /// DES key, also used as IV
+    pub encrypt_key: String,
+    pub ip_list: Vec<Ipv4Addr>,
+    #[serde(default)]
+    pub http: HttpConfig,
+}
+
+impl Connector {
+    pub fn new(config: SaConfig) -> Result<Self> {
+        Ok(Self {
+            http: HttpClient::new(config.http.clone())?,
+            config,
+        })
+    }
+
+    pub async fn get_bet_detail_url(
+        &self,
+        username: &Username,
+        bet_id: &ProviderBetID,
+    ) -> Result<Url> {
+        // SA expects its own GMT+8 time in every request
+        let time = OffsetDateTime::now_utc()
+            .to_offset(offset!(+8))
+            .format(format_description!(
+                "[year][month][day][hour][minute][second]"
+            ))
+            .context("Failed to format SA request time")?;
+
+        let query = serde_urlencoded::to_string(BetDetailQuery {
+            method: "GetBetDetailURL",
+            key: &self.config.secret_key,
+            time: &time,
+            username,
+            bet_id,
+        })
+        .context("Failed to serialize SA bet detail query")?;
+
+        let payload = ApiPayload {
+            q: crypto::des_cbc_encrypt(&query, &self.config.encrypt_key, &self.config.encrypt_key)
+                .context("Failed to DES-CBC encrypt payload for SA")?,
+            s: crypto::md5(format!(
+                "{query}{}{time}{}",
+                self.config.md5_key, self.config.secret_key
+            )),
+        };
+
+        let body = self
+            .http
+            .send(|client| {
+                Ok(client
+                    .post(format!("{}/api/api.aspx", &self.config.api_url))
+                    .form(&payload))
+            })
+            .await
+            .with_context(|| format!("Failed to fetch SA bet detail for '{}'", bet_id))?
+            .text()
+            .await
+            .with_context(|| format!("Failed to read SA bet detail response for '{}'", bet_id))?;
+
+        let result: BetDetailResponse = quick_xml::de::from_str(&body)
+            .with_context(|| format!("Failed to parse SA bet detail XML: '{}'", body))?;
+
+        match (result.error_msg_id, result.url) {
+            (0, Some(url)) => Ok(url),
+            _ => bail!("SA get bet detail returned error: '{}'", body),
+        }
+    }
+}
+
+#[async_trait]
+impl BetDetailsConnector for Connector {
+    async fn fetch_details(&self, bet: &BetDetailsRequest) -> Result<Option<BetDetails>> {
+        let url = self
+            .get_bet_detail_url(&bet.username, &bet.provider_bet_id)
+            .await?;
+
+        Ok(Some(BetDetails {
+            id: bet.id,
+            details: None,
+            replay: Some(url),
+        }))
+    }
+}
+
+#[derive(Serialize)]
+struct BetDetailQuery<'a> {
+    method: &'static str,
+    #[serde(rename = "Key")]
+    key: &'a str,
+    #[serde(rename = "Time")]
+    time: &'a str,
+    #[serde(rename = "Username")]
+    username: &'a Username,
+    #[serde(rename = "BetID")]
+    bet_id: &'a ProviderBetID,
+}
+
+#[derive(Serialize)]
+struct ApiPayload {
+    /// DES encrypted query
+    q: String,
+    /// MD5 signature of the plain query
+    s: String,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+struct BetDetailResponse {
+    error_msg_id: i64,
+    #[serde(rename = "URL")]
+    url: Option<Url>,
+}
@@ -0,0 +1,160 @@
+use std::net::Ipv4Addr;
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use log::warn;
+use rustc_hash::FxHashMap;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    archiver::bets::loader::{BetDetails, BetDetailsRequest},
+    connectors::{
+        http::{HttpClient, HttpConfig},
+        BetDetailsConnector,
+    },
+    enums::provider::ProviderGameKind,
+    types::{ProviderBetID, ProviderGameVendorID, Url, Username},
+};
+
+/// Live casino and slots are separate WM products, each with its own 'provider_config' row
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct WmConfig {
+    pub api_url: Url,
+    pub vendor_id: String,
+    pub signature: String,
+    pub ip_list: Vec<Ipv4Addr>,
+    #[serde(default)]
+    pub http: HttpConfig,
+}
+
+#[derive(Debug)]
+pub struct Connector {
+    config: WmConfig,
+    http: HttpClient,
+    kinds_by_vendor_id: FxHashMap<ProviderGameVendorID, ProviderGameKind>,
+}
+
+pub struct RoundHistory {
+    pub result_url: Url,
+    pub replay_url: Option<Url>,
+}
+
+impl Connector {
+    pub fn new(
+        config: WmConfig,
+        kinds_by_vendor_id: FxHashMap<ProviderGameVendorID, ProviderGameKind>,
+    ) -> Result<Self> {
+        Ok(Self {
+            http: HttpClient::new(config.http.clone())?,
+            config,
+            kinds_by_vendor_id,
+        })
+    }
+
+    /// `None` for games which WM keeps no history of. A game missing from 'provider_game'
+    /// won't be there on a retry either, so it is only logged instead of failing the bet.
+    pub async fn get_round_history(
+        &self,
+        username: &Username,
+        bet_id: &ProviderBetID,
+        game_vendor_id: &ProviderGameVendorID,
+    ) -> Result<Option<RoundHistory>> {
+        let Some(kind) = self.kinds_by_vendor_id.get(game_vendor_id) else {
+            warn!(
+                "Unknown WM game '{}' of bet '{}', details are skipped",
+                game_vendor_id, bet_id
+            );
+            return Ok(None);
+        };
+
+        let Some(endpoint) = history_endpoint(*kind) else {
+            return Ok(None);
+        };
+
+        let payload = RoundHistoryPayload {
+            vendor_id: &self.config.vendor_id,
+            signature: &self.config.signature,
+            user: username,
+            bet_id,
+        };
+
+        let response: Response = self
+            .http
+            .send(|client| {
+                Ok(client
+                    .get(format!("{}/history/{endpoint}", &self.config.api_url))
+                    .query(&payload))
+            })
+            .await
+            .with_context(|| format!("Failed to fetch WM round history for '{}'", bet_id))?
+            .json()
+            .await
+            .with_context(|| format!("Failed to parse WM round history for '{}'", bet_id))?;
+
+        match response.result {
+            Some(result) if response.error_code == 0 => Ok(Some(RoundHistory {
+                result_url: result.result_url,
+                replay_url: result.replay_url,
+            })),
+            _ => bail!(
+                "WM round history API error {}: {}",
+                response.error_code,
+                response.error_message
+            ),
+        }
+    }
+}
+
+/// Every table game has its own history endpoint
+fn history_endpoint(kind: ProviderGameKind) -> Option<&'static str> {
+    match kind {
+        ProviderGameKind::Baccarat => Some("baccarat"),
+        ProviderGameKind::DragonTiger => Some("dragon-tiger"),
+        ProviderGameKind::Roulette => Some("roulette"),
+        ProviderGameKind::Slot => Some("slot"),
+        _ => None,
+    }
+}
+
+#[async_trait]
+impl BetDetailsConnector for Connector {
+    async fn fetch_details(&self, bet: &BetDetailsRequest) -> Result<Option<BetDetails>> {
+        let history = self
+            .get_round_history(
+                &bet.username,
+                &bet.provider_bet_id,
+                &bet.provider_game_vendor_id,
+            )
+            .await?;
+
+        Ok(history.map(|history| BetDetails {
+            replay: history.replay_url,
+            ..BetDetails::with_result(bet.id, history.result_url)
+        }))
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RoundHistoryPayload<'a> {
+    vendor_id: &'a str,
+    signature: &'a str,
+    user: &'a Username,
+    bet_id: &'a ProviderBetID,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct Response {
+    error_code: i64,
+    error_message: String,
+    result: Option<RoundHistoryResult>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct RoundHistoryResult {
+    result_url: Url,
+    replay_url: Option<Url>,
+}
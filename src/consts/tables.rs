@@ -1,3 +1,4 @@
 pub const OPENING_BALANCE_TABLE_NAME: &str = "opening_balance";
 pub const CREDIT_DEBT_TABLE_NAME: &str = "credit_debt";
 pub const BET_DETAIL_REPORT_TABLE_NAME: &str = "bet_archive_details";
+pub const LOTTERY_BET_TABLE_NAME: &str = "bet_lottery";
@@ -6,7 +6,12 @@ use sqlx::{
     MySqlPool, PgPool,
 };
 
-pub async fn create_pg_connection() -> PgPool {
+/// Every provider worker holds a transaction and occasionally needs one more connection
+pub fn pool_size(concurrency: usize) -> u32 {
+    (concurrency as u32 * 2 + 1).max(5)
+}
+
+pub async fn create_pg_connection(max_connections: u32) -> PgPool {
     let connect_options = PgConnectOptions::new()
         .host(&env::var("DB_HOST").expect("DB_HOST is not set"))
         .port(
@@ -20,13 +25,13 @@ pub async fn create_pg_connection() -> PgPool {
         .password(&env::var("TYPEORM_PASSWORD").expect("TYPEORM_PASSWORD is not set"));
 
     PgPoolOptions::new()
-        .max_connections(5)
+        .max_connections(max_connections)
         .connect_with(connect_options)
         .await
         .expect("Failed to connect to PostgreSQL DB")
 }
 
-pub async fn create_mysql_connection() -> MySqlPool {
+pub async fn create_mysql_connection(max_connections: u32) -> MySqlPool {
     let connect_options = MySqlConnectOptions::new()
         .host(&env::var("MARIA_DB_HOST").expect("MARIA_DB_HOST is not set"))
         .port(
@@ -39,7 +44,7 @@ pub async fn create_mysql_connection() -> MySqlPool {
         .password(&env::var("MARIA_DB_PASSWORD").expect("MARIA_DB_PASSWORD is not set"));
 
     MySqlPoolOptions::new()
-        .max_connections(5)
+        .max_connections(max_connections)
         .connect_with(connect_options)
         .await
         .expect("Failed to connect to PostgreSQL DB")
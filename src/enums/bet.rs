@@ -1,6 +1,8 @@
 use strum_macros::{AsRefStr, Display, EnumString, VariantArray};
 
-#[derive(sqlx::Type, Clone, Copy, Display, AsRefStr, VariantArray, EnumString)]
+#[derive(
+    sqlx::Type, Clone, Copy, PartialEq, Eq, Hash, Debug, Display, AsRefStr, VariantArray, EnumString,
+)]
 #[sqlx(rename_all = "UPPERCASE", type_name = "bet_status_enum")]
 #[strum(serialize_all = "UPPERCASE")]
 pub enum BetStatus {
@@ -1,6 +1,6 @@
-use strum_macros::{AsRefStr, Display, VariantArray};
+use strum_macros::{AsRefStr, Display, EnumString, VariantArray};
 
-#[derive(PartialEq, Eq, Clone, Copy, Debug, AsRefStr, Display, VariantArray)]
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, AsRefStr, Display, EnumString, VariantArray)]
 #[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
 pub enum Product {
     LiveCasino,
@@ -1,7 +1,9 @@
 use serde::{Deserialize, Serialize};
-use strum_macros::{Display, VariantArray};
+use strum_macros::{Display, EnumString, VariantArray};
 
-#[derive(Debug, Serialize, Deserialize, Display, PartialEq, Eq, Clone, Copy, VariantArray)]
+#[derive(
+    Debug, Serialize, Deserialize, Display, EnumString, PartialEq, Eq, Clone, Copy, VariantArray,
+)]
 pub enum ProviderGameKind {
     #[serde(rename = "baccarat")]
     #[strum(serialize = "baccarat")]
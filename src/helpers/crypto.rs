@@ -3,6 +3,7 @@ use base64::{engine::general_purpose, Engine};
 use md5::{Digest, Md5};
 use openssl::symm::{Cipher, Crypter, Mode};
 use std::str;
+use time::{macros::format_description, macros::offset, OffsetDateTime};
 
 pub fn des_cbc_encrypt(target: &str, key: &str, iv: &str) -> Result<String> {
     let _provider = openssl::provider::Provider::try_load(None, "legacy", true).unwrap();
@@ -49,3 +50,21 @@ pub fn md5(data: String) -> String {
     hasher.update(data);
     hex::encode(hasher.finalize())
 }
+
+/// Jili key of a request. Its MD5 part depends on the current date in GMT-4,
+/// so `now` is passed in to keep the key reproducible.
+/// The 6 characters around the hash are ignored by Jili and kept constant.
+pub fn jili_key(
+    query: &str,
+    now: OffsetDateTime,
+    agent_id: &str,
+    agent_key: &str,
+) -> Result<String> {
+    let date = now.to_offset(offset!(-4)).format(format_description!(
+        "[year repr:last_two][month][day padding:none]"
+    ))?;
+
+    let key_g = md5(format!("{date}{agent_id}{agent_key}"));
+
+    Ok(format!("000000{}000000", md5(format!("{query}{key_g}"))))
+}
@@ -1,23 +1,85 @@
+use std::io::Write;
+
 use anyhow::{Context, Result};
+use serde_json::json;
 use sqlx::PgPool;
 use time::OffsetDateTime;
 
+use crate::cli::LogFormat;
+
+pub fn init_logger(format: LogFormat) {
+    // Run progress is logged at info level unless RUST_LOG says otherwise
+    let mut builder =
+        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info"));
+
+    if let LogFormat::Json = format {
+        builder.format(|buf, record| {
+            writeln!(
+                buf,
+                "{}",
+                json!({
+                    "timestamp": OffsetDateTime::now_utc().unix_timestamp(),
+                    "level": record.level().as_str(),
+                    "target": record.target(),
+                    "message": record.args().to_string(),
+                })
+            )
+        });
+    }
+
+    builder.init();
+}
+
 pub async fn log_error(pg: &PgPool, err: anyhow::Error) -> Result<()> {
+    insert_log(pg, format!("{:?}", err), ErrorKind::Error, None)
+        .await
+        .with_context(|| format!("Failed to log error: '{err}'. Error: {}", err))
+}
+
+pub async fn log_warning(pg: &PgPool, description: String) -> Result<()> {
+    insert_log(pg, description, ErrorKind::Warning, None)
+        .await
+        .context("Failed to log warning")
+}
+
+/// Same as `log_warning`, with structured JSON details in 'payload' column
+pub async fn log_warning_with_payload(
+    pg: &PgPool,
+    description: String,
+    payload: serde_json::Value,
+) -> Result<()> {
+    insert_log(
+        pg,
+        description,
+        ErrorKind::Warning,
+        Some(payload.to_string()),
+    )
+    .await
+    .context("Failed to log warning")
+}
+
+async fn insert_log(
+    pg: &PgPool,
+    description: String,
+    kind: ErrorKind,
+    payload: Option<String>,
+) -> Result<()> {
     sqlx::query!(
         r#"
             INSERT INTO public.system_log (
                 description,
                 date,
-                kind
-            ) VALUES ($1, $2, $3)
+                kind,
+                payload
+            ) VALUES ($1, $2, $3, $4)
         "#,
-        format!("{:?}", err),
+        description,
         OffsetDateTime::now_utc(),
-        ErrorKind::Error as i32,
+        kind as i32,
+        payload,
     )
     .execute(pg)
-    .await
-    .with_context(|| format!("Failed to log error: '{err}'. Error: {}", err))?;
+    .await?;
 
     Ok(())
 }
@@ -0,0 +1,178 @@
+use std::{
+    fs,
+    future::Future,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+use lazy_static::lazy_static;
+use log::error;
+use prometheus::{
+    register_gauge, register_histogram_vec, register_int_counter_vec, Encoder, Gauge,
+    HistogramTimer, HistogramVec, IntCounterVec, TextEncoder,
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+
+use crate::enums::provider::GameProvider;
+
+/// Scrapers send their request right away, a silent connection is dropped after this
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+lazy_static! {
+    static ref CHUNK_DURATION: HistogramVec = register_histogram_vec!(
+        "archiver_chunk_duration_seconds",
+        "Time spent on a single bet chunk",
+        &["provider"]
+    )
+    .unwrap();
+    static ref BETS_ARCHIVED: IntCounterVec = register_int_counter_vec!(
+        "archiver_bets_archived_total",
+        "Bets moved from PG to MariaDB",
+        &["provider"]
+    )
+    .unwrap();
+    static ref CONNECTOR_REQUEST_DURATION: HistogramVec = register_histogram_vec!(
+        "archiver_connector_request_duration_seconds",
+        "Latency of provider API requests",
+        &["connector"]
+    )
+    .unwrap();
+    static ref CONNECTOR_ERRORS: IntCounterVec = register_int_counter_vec!(
+        "archiver_connector_errors_total",
+        "Failed provider API requests",
+        &["connector"]
+    )
+    .unwrap();
+    static ref DB_QUERY_DURATION: HistogramVec = register_histogram_vec!(
+        "archiver_db_query_duration_seconds",
+        "Duration of PG and MariaDB queries",
+        &["db", "query"]
+    )
+    .unwrap();
+    static ref RUN_DURATION: Gauge =
+        register_gauge!("archiver_run_duration_seconds", "Duration of the last run").unwrap();
+    static ref RUN_SUCCESS: Gauge = register_gauge!(
+        "archiver_run_success",
+        "1 if the last run succeeded, 0 otherwise"
+    )
+    .unwrap();
+    static ref RUN_FINISHED_AT: Gauge = register_gauge!(
+        "archiver_run_finished_timestamp_seconds",
+        "Unix time when the last run finished"
+    )
+    .unwrap();
+}
+
+pub fn observe_chunk(provider: GameProvider, started_at: Instant, bets_archived: usize) {
+    let provider = provider.to_string();
+
+    CHUNK_DURATION
+        .with_label_values(&[&provider])
+        .observe(started_at.elapsed().as_secs_f64());
+
+    BETS_ARCHIVED
+        .with_label_values(&[&provider])
+        .inc_by(bets_archived as u64);
+}
+
+/// Observes the query duration when the returned timer is dropped
+pub fn db_query_timer(db: &str, query: &str) -> HistogramTimer {
+    DB_QUERY_DURATION
+        .with_label_values(&[db, query])
+        .start_timer()
+}
+
+pub async fn observe_connector<T>(
+    connector: &str,
+    request: impl Future<Output = Result<T>>,
+) -> Result<T> {
+    let timer = CONNECTOR_REQUEST_DURATION
+        .with_label_values(&[connector])
+        .start_timer();
+
+    let result = request.await;
+    timer.observe_duration();
+
+    if result.is_err() {
+        CONNECTOR_ERRORS.with_label_values(&[connector]).inc();
+    }
+
+    result
+}
+
+pub fn observe_run(started_at: Instant, succeeded: bool) {
+    RUN_DURATION.set(started_at.elapsed().as_secs_f64());
+    RUN_SUCCESS.set(if succeeded { 1.0 } else { 0.0 });
+    RUN_FINISHED_AT.set(time::OffsetDateTime::now_utc().unix_timestamp() as f64);
+}
+
+fn encode() -> Result<Vec<u8>> {
+    let mut buffer = vec![];
+
+    TextEncoder::new()
+        .encode(&prometheus::gather(), &mut buffer)
+        .context("Failed to encode metrics")?;
+
+    Ok(buffer)
+}
+
+/// Textfile collector may read the file at any moment, so it's replaced atomically
+pub fn write_textfile(path: &str) -> Result<()> {
+    let tmp_path = format!("{path}.tmp");
+
+    fs::write(&tmp_path, encode()?)
+        .with_context(|| format!("Failed to write metrics to '{tmp_path}'"))?;
+
+    fs::rename(&tmp_path, path).with_context(|| format!("Failed to move metrics file to '{path}'"))
+}
+
+/// Serves metrics on `GET /metrics` until the process exits
+pub async fn serve(addr: &str) -> Result<()> {
+    let listener = TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Failed to bind metrics listener to '{addr}'"))?;
+
+    loop {
+        let (socket, _) = listener
+            .accept()
+            .await
+            .context("Failed to accept metrics connection")?;
+
+        // A slow client must not hold back the next scrape
+        tokio::spawn(respond(socket));
+    }
+}
+
+async fn respond(mut socket: TcpStream) {
+    let mut request = [0; 1024];
+
+    let Ok(Ok(read)) = tokio::time::timeout(READ_TIMEOUT, socket.read(&mut request)).await else {
+        return;
+    };
+
+    let request_line = String::from_utf8_lossy(&request[..read]);
+
+    let (status, body) = match request_line.split_whitespace().take(2).collect::<Vec<_>>()[..] {
+        ["GET", "/metrics"] => match encode() {
+            Ok(body) => ("200 OK", body),
+            Err(e) => {
+                error!("{:?}", e);
+                ("500 Internal Server Error", vec![])
+            }
+        },
+        ["GET", _] => ("404 Not Found", vec![]),
+        _ => ("405 Method Not Allowed", vec![]),
+    };
+
+    let header = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        TextEncoder::new().format_type(),
+        body.len()
+    );
+
+    let _ = socket.write_all(header.as_bytes()).await;
+    let _ = socket.write_all(&body).await;
+}
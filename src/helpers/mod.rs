@@ -1,27 +1,38 @@
 pub mod crypto;
 pub mod logger;
+pub mod metrics;
 pub mod provider;
 pub mod query_helper;
 mod time;
 
+use std::sync::Mutex;
+
 use ::time::Date;
 use rustc_hash::FxHashMap;
 use sqlx::{MySqlPool, PgPool};
 pub use time::*;
+use uuid::Uuid;
 
 use crate::{
-    archiver::bets::loader::User,
+    archiver::{bets::loader::User, dry_run::DryRunReport, options::RunOptions, summary::RunStats},
     connectors::Connectors,
     types::{UserID, Username},
 };
 
 #[derive(Debug)]
 pub struct State {
+    /// Filled before providers are archived, read-only afterwards
     pub credit_players: FxHashMap<UserID, bool>,
-    pub username_by_user_id: FxHashMap<UserID, Username>,
-    pub upline: FxHashMap<UserID, Vec<User>>,
+    // Caches below are shared between providers archived concurrently
+    pub username_by_user_id: Mutex<FxHashMap<UserID, Username>>,
+    pub upline: Mutex<FxHashMap<UserID, Vec<User>>>,
     pub wl_by_date_by_user: FxHashMap<Date, FxHashMap<UserID, i64>>,
     pub connectors: Connectors,
+    pub options: RunOptions,
+    pub dry_run_report: Mutex<DryRunReport>,
+    /// Journal run of the whole pipeline. Not set for single stage commands.
+    pub run_id: Option<Uuid>,
+    pub stats: Mutex<RunStats>,
     pub pg: PgPool,
     pub maria_db: MySqlPool,
 }
@@ -30,9 +41,13 @@ impl State {
     pub fn new(connectors: Connectors, pg: PgPool, mysql: MySqlPool) -> Self {
         Self {
             connectors,
+            options: RunOptions::default(),
+            dry_run_report: Mutex::default(),
+            run_id: None,
+            stats: Mutex::default(),
             credit_players: FxHashMap::default(),
-            username_by_user_id: FxHashMap::default(),
-            upline: FxHashMap::default(),
+            username_by_user_id: Mutex::default(),
+            upline: Mutex::default(),
             wl_by_date_by_user: FxHashMap::default(),
             pg,
             maria_db: mysql,
@@ -1,6 +1,6 @@
 use time::Date;
 
-use crate::enums::provider::GameProvider;
+use crate::{consts::LOTTERY_BET_TABLE_NAME, enums::provider::GameProvider};
 
 pub fn get_archive_schema_name(date: impl Into<Date>) -> String {
     format!("archive_{}", date.into().year())
@@ -27,7 +27,7 @@ pub fn get_double_digit_month(date: Date) -> String {
 
 pub fn get_bet_table_name(provider: GameProvider) -> String {
     match provider {
-        GameProvider::Lottery(_) => "bet_lottery".to_string(),
+        GameProvider::Lottery(_) => LOTTERY_BET_TABLE_NAME.to_string(),
         _ => {
             format!("bet_{}", provider.as_ref())
         }
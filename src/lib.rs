@@ -1,4 +1,5 @@
 pub mod archiver;
+pub mod cli;
 pub mod connectors;
 pub mod consts;
 pub mod db;
@@ -1,24 +1,37 @@
-use claims::assert_ok;
+use claims::{assert_err, assert_ok};
 use dotenvy::dotenv;
-use lib::archiver::run;
+use lib::archiver::summary::{finish_run_summary, format_recent_runs, start_run_summary, RunStats};
+use lib::archiver::{
+    archive_provider,
+    bets::{
+        details::log_details_failure,
+        loader::Bet,
+        pending_details::{enqueue_pending_details, retry_pending_details},
+    },
+    run,
+};
 use lib::connectors::load_connectors;
 use lib::consts::{CREDIT_DEBT_TABLE_NAME, OPENING_BALANCE_TABLE_NAME};
-use lib::enums::provider::GameProvider;
+use lib::enums::provider::{
+    GameProvider, LiveCasinoProvider, Lottery, OnlineCasinoProvider, SlotProvider, Sportsbook,
+};
 use lib::enums::PositionEnum;
 use lib::helpers::query_helper::{
     get_archive_schema_name, get_bet_table_name, get_dynamic_table_name,
 };
 use lib::helpers::{add_month, get_hong_kong_11_hours_from_date, State};
-use lib::types::UserID;
+use lib::types::{BetID, ProviderBetID, UserID};
 use sqlx::prelude::FromRow;
-use sqlx::{PgPool, Row};
+use sqlx::{Executor, MySqlPool, PgPool, Row};
 use time::{Date, Duration, OffsetDateTime};
+use uuid::Uuid;
 
 use crate::helper::db::{create_maria_db_test_connection, create_pg_test_connection};
 use crate::helper::mock_servers::mount_mock_servers;
-use crate::helper::test_data::{get_yesterday_11, prepare_data, TEST_PROVIDERS};
+use crate::helper::test_data::{get_yesterday_11, prepare_data, TestData, TEST_PROVIDERS};
 
 mod create_benchmark_data;
+mod retention;
 
 #[tokio::test]
 async fn test_procedure() {
@@ -32,21 +45,33 @@ async fn test_procedure() {
     let t_data = prepare_data(&pg_pool, &maria_db_pool, start_date, Duration::minutes(30)).await;
     mount_mock_servers(&t_data).await;
 
-    let connectors = load_connectors(&pg_pool).await.unwrap();
+    let connectors = load_connectors(&pg_pool, true).await.unwrap();
     let mut state = State::new(connectors, pg_pool, maria_db_pool);
 
+    assert_dry_run_writes_nothing(&mut state, &t_data, start_date).await;
+    assert_window_archives_only_its_bets(&mut state, &t_data).await;
+
+    let mismatching = plant_mismatching_maria_db_copy(&state.maria_db, &t_data).await;
+
     let result = run(&mut state).await;
     assert_ok!(result);
 
+    assert_mismatching_bet_kept_in_pg(&state, mismatching).await;
+
     // Check opening balance
     let mut total_wl: i64 = 0;
     let yesterday11 =
         get_hong_kong_11_hours_from_date(OffsetDateTime::now_utc().date() - Duration::days(1));
 
     let mut all_credit_bets = vec![];
+    let mut archived_bets_count: i64 = 0;
 
     for (_, bets) in &t_data.bets_by_provider {
         for bet in bets {
+            if bet.last_status_change < yesterday11 {
+                archived_bets_count += 1;
+            }
+
             if bet.user_id == t_data.credit_player.id && bet.last_status_change < yesterday11 {
                 all_credit_bets.push(bet.clone());
                 total_wl += bet.wl.unwrap_or(0);
@@ -64,6 +89,31 @@ async fn test_procedure() {
         assert_eq!(count, 0);
     }
 
+    assert_eq!(
+        archived_bets_count,
+        count_maria_db_bets(&state.maria_db).await
+    );
+
+    // Lottery kinds share one table, but every bet is attributed to its own kind
+    for provider in TEST_PROVIDERS {
+        if let GameProvider::Lottery(_) = provider {
+            let expected: Vec<&Bet> = t_data.bets_by_provider[&provider]
+                .iter()
+                .filter(|bet| bet.last_status_change < yesterday11)
+                .collect();
+
+            let (count, wl) = get_maria_db_provider_figures(&state.maria_db, provider).await;
+
+            assert_eq!(count, expected.len() as i64);
+            assert_eq!(
+                wl,
+                expected.iter().map(|bet| bet.wl.unwrap_or(0)).sum::<i64>()
+            );
+        }
+    }
+
+    assert_stored_details(&state.maria_db).await;
+
     let debts = get_debts_from_date(&state.pg, start_date).await;
 
     for debt in debts {
@@ -86,6 +136,480 @@ async fn test_procedure() {
 
         assert_eq!(expected_debt_amount, debt.debt_amount);
     }
+
+    assert_details_failure_payload(&state.pg).await;
+    assert_interrupted_run_resumes(&mut state).await;
+    assert_run_summaries(&state.pg).await;
+    assert_pending_details_retry(&state, &t_data).await;
+    assert_missing_config_disables_only_its_provider(&state, &t_data).await;
+}
+
+/// Lenient load skips the provider without config and keeps every other connector.
+/// Queued details of the disabled provider wait until its connector is loaded again.
+async fn assert_missing_config_disables_only_its_provider(state: &State, t_data: &TestData) {
+    let pg = &state.pg;
+    let missing = GameProvider::Slot(SlotProvider::Ameba);
+    let kept = GameProvider::OnlineCasino(OnlineCasinoProvider::Arcadia);
+
+    sqlx::query("DELETE FROM public.provider_config WHERE game_provider = $1")
+        .bind(missing.as_ref())
+        .execute(pg)
+        .await
+        .unwrap();
+
+    assert_err!(load_connectors(pg, true).await);
+
+    let connectors = load_connectors(pg, false).await.unwrap();
+
+    assert!(connectors.get(missing).is_none());
+    assert!(connectors.is_disabled(missing));
+    assert!(connectors.get(kept).is_some());
+
+    let warnings: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM public.system_log WHERE description LIKE $1")
+            .bind(format!("Details of '{}' are disabled%", missing.as_ref()))
+            .fetch_one(pg)
+            .await
+            .unwrap();
+
+    assert_eq!(warnings, 1);
+
+    let lenient_state = State::new(connectors, pg.clone(), state.maria_db.clone());
+    let waiting = Bet {
+        id: BetID(Uuid::new_v4()),
+        ..t_data.bets_by_provider[&missing][0].clone()
+    };
+
+    let mut pg_transaction = pg.begin().await.unwrap();
+    let failed = vec![(&waiting, "Timeout".to_string())];
+    assert_ok!(enqueue_pending_details(&mut pg_transaction, missing, failed).await);
+    pg_transaction.commit().await.unwrap();
+
+    sqlx::query(
+        "UPDATE public.pending_bet_details SET next_attempt_at = now() - interval '1 minute' WHERE bet_id = $1",
+    )
+    .bind(waiting.id)
+    .execute(pg)
+    .await
+    .unwrap();
+
+    assert_ok!(retry_pending_details(&lenient_state, Duration::days(30)).await);
+
+    let (attempts, last_error): (i32, String) = sqlx::query_as(
+        "SELECT attempts, last_error FROM public.pending_bet_details WHERE bet_id = $1",
+    )
+    .bind(waiting.id)
+    .fetch_one(pg)
+    .await
+    .unwrap();
+
+    assert_eq!(attempts, 0);
+    assert!(last_error.contains("connector is not loaded"));
+}
+
+/// Queued details are deleted once fetched, rescheduled with backoff on failure
+/// and marked dead when they can never be retried
+async fn assert_pending_details_retry(state: &State, t_data: &TestData) {
+    let bet = &t_data.bets_by_provider[&TEST_PROVIDERS[0]][0];
+
+    let fetched = Bet {
+        id: BetID(Uuid::new_v4()),
+        ..bet.clone()
+    };
+    // Royal Slot Gaming connector fails before any request on a round id which is not a number
+    let failing = Bet {
+        id: BetID(Uuid::new_v4()),
+        provider_bet_id: ProviderBetID("not_a_number".to_string()),
+        ..bet.clone()
+    };
+    let dead = Bet {
+        id: BetID(Uuid::new_v4()),
+        ..bet.clone()
+    };
+
+    let mut pg_transaction = state.pg.begin().await.unwrap();
+
+    for (provider, bet) in [
+        (GameProvider::Slot(SlotProvider::Ameba), &fetched),
+        (GameProvider::Slot(SlotProvider::RoyalSlotGaming), &failing),
+        (GameProvider::Lottery(Lottery::Thai), &dead),
+    ] {
+        let failed = vec![(bet, "Timeout".to_string())];
+        assert_ok!(enqueue_pending_details(&mut pg_transaction, provider, failed).await);
+    }
+
+    pg_transaction.commit().await.unwrap();
+
+    let ids = vec![fetched.id.0, failing.id.0, dead.id.0];
+
+    sqlx::query(
+        "UPDATE public.pending_bet_details SET next_attempt_at = now() - interval '1 minute' WHERE bet_id = ANY($1)",
+    )
+    .bind(&ids)
+    .execute(&state.pg)
+    .await
+    .unwrap();
+
+    sqlx::query(
+        "UPDATE public.pending_bet_details SET provider = 'removed_provider' WHERE bet_id = $1",
+    )
+    .bind(dead.id)
+    .execute(&state.pg)
+    .await
+    .unwrap();
+
+    assert_ok!(retry_pending_details(state, Duration::days(30)).await);
+
+    let rows = sqlx::query(
+        "SELECT bet_id, attempts, next_attempt_at, last_error, dead_at FROM public.pending_bet_details WHERE bet_id = ANY($1)",
+    )
+    .bind(&ids)
+    .fetch_all(&state.pg)
+    .await
+    .unwrap();
+
+    assert_eq!(rows.len(), 2);
+
+    let fetched_details: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM public.bet_archive_details WHERE id = ?")
+            .bind(fetched.id.to_string())
+            .fetch_one(&state.maria_db)
+            .await
+            .unwrap();
+
+    assert_eq!(fetched_details, 1);
+
+    let row = |id: BetID| {
+        rows.iter()
+            .find(|row| row.get::<Uuid, _>("bet_id") == id.0)
+            .unwrap()
+    };
+
+    let failing_row = row(failing.id);
+    assert_eq!(failing_row.get::<i32, _>("attempts"), 1);
+    assert!(
+        failing_row.get::<OffsetDateTime, _>("next_attempt_at")
+            > OffsetDateTime::now_utc() + Duration::minutes(9)
+    );
+    assert!(failing_row
+        .get::<String, _>("last_error")
+        .contains("Expected royal slot bet to have a number"));
+    assert!(failing_row
+        .get::<Option<OffsetDateTime>, _>("dead_at")
+        .is_none());
+
+    let dead_row = row(dead.id);
+    assert_eq!(dead_row.get::<i32, _>("attempts"), 0);
+    assert!(dead_row
+        .get::<Option<OffsetDateTime>, _>("dead_at")
+        .is_some());
+
+    let warnings: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM public.system_log WHERE description LIKE $1")
+            .bind(format!("Stopped retrying details of bet '{}'%", dead.id))
+            .fetch_one(&state.pg)
+            .await
+            .unwrap();
+
+    assert_eq!(warnings, 1);
+}
+
+/// Failed detail fetches are logged with the bet, provider and error chain as JSON
+async fn assert_details_failure_payload(pg: &PgPool) {
+    let bet_id = BetID(Uuid::new_v4());
+    let provider = GameProvider::Slot(SlotProvider::Ameba);
+    let err = anyhow::anyhow!("Connection refused").context("Failed to request Ameba history");
+
+    log_details_failure(pg, bet_id, provider, err).await;
+
+    let payload: String =
+        sqlx::query_scalar("SELECT payload FROM public.system_log WHERE description = $1")
+            .bind(format!(
+                "Failed to fetch details of bet '{bet_id}' from '{provider}'"
+            ))
+            .fetch_one(pg)
+            .await
+            .unwrap();
+
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(&payload).unwrap(),
+        serde_json::json!({
+            "bet_id": bet_id.to_string(),
+            "provider": provider.to_string(),
+            "error": ["Failed to request Ameba history", "Connection refused"],
+        })
+    );
+}
+
+/// A run interrupted after its bets stage only finishes the details sync
+async fn assert_interrupted_run_resumes(state: &mut State) {
+    let interrupted_id: Uuid = sqlx::query_scalar(
+        "INSERT INTO public.archive_run (cutoff_date, stage, started_at) VALUES ($1, 'details_sync', now()) RETURNING id",
+    )
+    .bind(state.options.cutoff_date)
+    .fetch_one(&state.pg)
+    .await
+    .unwrap();
+
+    let runs_before = count_archive_runs(&state.pg).await;
+
+    assert_ok!(run(state).await);
+
+    assert_eq!(state.run_id, Some(interrupted_id));
+    assert_eq!(count_archive_runs(&state.pg).await, runs_before);
+
+    let (stage, finished_at): (String, Option<OffsetDateTime>) =
+        sqlx::query_as("SELECT stage, finished_at FROM public.archive_run WHERE id = $1")
+            .bind(interrupted_id)
+            .fetch_one(&state.pg)
+            .await
+            .unwrap();
+
+    assert_eq!(stage, "done");
+    assert!(finished_at.is_some());
+}
+
+async fn count_archive_runs(pg: &PgPool) -> i64 {
+    sqlx::query_scalar("SELECT COUNT(*) FROM public.archive_run")
+        .fetch_one(pg)
+        .await
+        .unwrap()
+}
+
+async fn assert_run_summaries(pg: &PgPool) {
+    let interrupted_id = start_run_summary(pg, "run").await.unwrap();
+
+    // Started by a process which was killed two days ago
+    sqlx::query(
+        "UPDATE public.archive_run_summary SET started_at = started_at - interval '2 days' WHERE id = $1",
+    )
+    .bind(interrupted_id)
+    .execute(pg)
+    .await
+    .unwrap();
+
+    let live_id = start_run_summary(pg, "details-sync").await.unwrap();
+    let failed_id = start_run_summary(pg, "bets").await.unwrap();
+
+    let provider = GameProvider::Slot(SlotProvider::Ameba);
+    let mut stats = RunStats::default();
+    stats.provider(provider).bets_archived = 3;
+    stats.provider(provider).details_failed = 1;
+
+    let result = Err(anyhow::anyhow!("Connector failed"));
+    assert_ok!(finish_run_summary(pg, failed_id, None, &result, stats).await);
+
+    // Starts mark only the stale run as interrupted, the live one may belong to another process
+    let output = format_recent_runs(pg, 10).await.unwrap();
+    let lines: Vec<&str> = output.lines().collect();
+
+    assert!(lines[0].starts_with(&format!("{failed_id} bets: failed, started ")));
+    assert_eq!(lines[1], "  error: Connector failed");
+    assert!(lines[2].starts_with(&format!(
+        "  {provider}: 3 bets, 0 details fetched, 1 failed, 0 skipped"
+    )));
+    assert!(lines[3].starts_with(&format!("{live_id} details-sync: running, started ")));
+    assert!(lines[4].starts_with(&format!("{interrupted_id} run: interrupted, started ")));
+    assert!(lines[4].ends_with("finished -"));
+    assert_eq!(
+        lines[5],
+        "  error: Run stopped before its summary was saved"
+    );
+    assert_eq!(lines.len(), 6);
+}
+
+/// Only bets inside the `--from`/`--to` window are archived, the ones before and after it stay in PG
+async fn assert_window_archives_only_its_bets(state: &mut State, t_data: &TestData) {
+    let today = OffsetDateTime::now_utc().date();
+    let from = today - Duration::days(6);
+    let to = today - Duration::days(4);
+    let (from11, to11) = (
+        get_hong_kong_11_hours_from_date(from),
+        get_hong_kong_11_hours_from_date(to),
+    );
+
+    let mut expected = (0, 0, 0);
+
+    for bet in t_data.bets_by_provider.values().flatten() {
+        match bet.last_status_change {
+            lsc if lsc < from11 => expected.0 += 1,
+            lsc if lsc < to11 => expected.1 += 1,
+            _ => expected.2 += 1,
+        }
+    }
+
+    assert!(expected.0 > 0 && expected.1 > 0 && expected.2 > 0);
+
+    let options = state.options.clone();
+    state.options.start_date = Some(from);
+    state.options.cutoff_date = to;
+
+    assert_ok!(run(state).await);
+
+    state.options = options;
+
+    assert_eq!(
+        count_pg_bets_by_window(&state.pg, from11, to11).await,
+        (expected.0, 0, expected.2)
+    );
+    assert_eq!(count_maria_db_bets(&state.maria_db).await, expected.1);
+}
+
+/// Bets before, inside and after a window, each shared table is counted once
+async fn count_pg_bets_by_window(
+    pg: &PgPool,
+    from11: OffsetDateTime,
+    to11: OffsetDateTime,
+) -> (i64, i64, i64) {
+    let mut tables = vec![];
+
+    for provider in TEST_PROVIDERS {
+        let table = get_bet_table_name(provider);
+
+        if !tables.contains(&table) {
+            tables.push(table);
+        }
+    }
+
+    let mut counts = (0, 0, 0);
+
+    for table in tables {
+        let (before, inside, after): (i64, i64, i64) = sqlx::query_as(&format!(
+            r#"
+                SELECT
+                    COUNT(*) FILTER (WHERE last_status_change < $1),
+                    COUNT(*) FILTER (WHERE last_status_change >= $1 AND last_status_change < $2),
+                    COUNT(*) FILTER (WHERE last_status_change >= $2)
+                FROM public.{table}
+            "#
+        ))
+        .bind(from11)
+        .bind(to11)
+        .fetch_one(pg)
+        .await
+        .unwrap();
+
+        counts = (counts.0 + before, counts.1 + inside, counts.2 + after);
+    }
+
+    counts
+}
+
+/// Simulates a faulty MariaDB copy: the trigger changes the stake of one bet on insert
+async fn plant_mismatching_maria_db_copy(
+    maria_db: &MySqlPool,
+    t_data: &TestData,
+) -> (GameProvider, Bet) {
+    let provider = GameProvider::Sport(Sportsbook::SingleNonLive);
+    let yesterday11 = get_yesterday_11();
+
+    // The latest archived bet, so it is still in PG after any earlier window
+    let bet = t_data.bets_by_provider[&provider]
+        .iter()
+        .filter(|bet| bet.user_id == t_data.cash_player.id && bet.last_status_change < yesterday11)
+        .max_by_key(|bet| bet.last_status_change)
+        .expect("No archived bet of the cash player")
+        .clone();
+
+    // Trigger DDL can't be prepared, so it is sent as a plain query
+    maria_db
+        .execute(
+            format!(
+                "CREATE OR REPLACE TRIGGER public.mismatching_bet_copy BEFORE INSERT ON public.bet FOR EACH ROW SET NEW.stake = IF(NEW.id = '{}', NEW.stake + 1, NEW.stake)",
+                bet.id
+            )
+            .as_str(),
+        )
+        .await
+        .unwrap();
+
+    (provider, bet)
+}
+
+/// The bet with a mismatching copy stays in PG without a copy in MariaDB,
+/// and is archived once the copy is correct
+async fn assert_mismatching_bet_kept_in_pg(state: &State, (provider, bet): (GameProvider, Bet)) {
+    let table = get_bet_table_name(provider);
+
+    let count_in_pg = || async {
+        sqlx::query_scalar::<_, i64>(&format!(
+            "SELECT COUNT(*) FROM public.{table} WHERE id = $1"
+        ))
+        .bind(bet.id)
+        .fetch_one(&state.pg)
+        .await
+        .unwrap()
+    };
+
+    assert_eq!(count_in_pg().await, 1);
+
+    let copies: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM public.bet WHERE id = ?")
+        .bind(bet.id.to_string())
+        .fetch_one(&state.maria_db)
+        .await
+        .unwrap();
+
+    assert_eq!(copies, 0);
+
+    let payloads: Vec<String> =
+        sqlx::query_scalar("SELECT payload FROM public.system_log WHERE description = $1")
+            .bind(format!(
+                "1 bets of '{provider}' were kept in PG, their MariaDB copies don't match"
+            ))
+            .fetch_all(&state.pg)
+            .await
+            .unwrap();
+
+    assert_eq!(payloads.len(), 1);
+
+    let payload: serde_json::Value = serde_json::from_str(&payloads[0]).unwrap();
+    assert_eq!(payload[0]["bet_id"], bet.id.to_string());
+    assert!(payload[0]["reason"]
+        .as_str()
+        .unwrap()
+        .starts_with("stake mismatch"));
+
+    state
+        .maria_db
+        .execute("DROP TRIGGER public.mismatching_bet_copy")
+        .await
+        .unwrap();
+
+    assert_ok!(archive_provider(provider, state).await);
+    assert_eq!(count_in_pg().await, 0);
+}
+
+async fn assert_dry_run_writes_nothing(state: &mut State, t_data: &TestData, start_date: Date) {
+    let mut pg_bets_before = vec![];
+
+    for provider in TEST_PROVIDERS {
+        pg_bets_before.push(count_bets_before_yesterday11(&state.pg, provider).await);
+    }
+
+    let opening_balance_before =
+        get_last_opening_balance_amount(&state.pg, t_data.credit_player.id).await;
+    let debts_before = get_debts_from_date(&state.pg, start_date).await.len();
+
+    state.options.dry_run = true;
+    assert_ok!(run(state).await);
+    state.options.dry_run = false;
+
+    let mut pg_bets_after = vec![];
+
+    for provider in TEST_PROVIDERS {
+        pg_bets_after.push(count_bets_before_yesterday11(&state.pg, provider).await);
+    }
+
+    assert_eq!(pg_bets_before, pg_bets_after);
+    assert_eq!(count_maria_db_bets(&state.maria_db).await, 0);
+    assert_eq!(
+        opening_balance_before,
+        get_last_opening_balance_amount(&state.pg, t_data.credit_player.id).await
+    );
+    assert_eq!(
+        debts_before,
+        get_debts_from_date(&state.pg, start_date).await.len()
+    );
 }
 
 async fn get_last_opening_balance_amount(pg: &PgPool, user_id: UserID) -> i64 {
@@ -140,6 +664,101 @@ async fn count_bets_before_yesterday11(pg: &PgPool, provider: GameProvider) -> i
     count
 }
 
+async fn count_maria_db_bets(maria_db: &MySqlPool) -> i64 {
+    let result = sqlx::query("SELECT COUNT(*) as count FROM public.bet")
+        .fetch_one(maria_db)
+        .await
+        .expect("Failed to count bets in Maria DB");
+
+    result
+        .try_get("count")
+        .expect("Failed to get count from 'count_maria_db_bets'")
+}
+
+/// Details are copied from 'bet_archive_details' into the bet table, which is truncated after that,
+/// so they are checked where they end up
+async fn assert_stored_details(maria_db: &MySqlPool) {
+    let url = Some("http://localhost");
+
+    let result = Some(r#"{"result":"http://localhost"}"#);
+
+    // Every WM game kind has its own history endpoint
+    let expected = [
+        (GameProvider::LiveCasino(LiveCasinoProvider::SA), None, url),
+        (
+            GameProvider::LiveCasino(LiveCasinoProvider::WM),
+            Some(r#"{"result":"http://localhost/baccarat"}"#),
+            url,
+        ),
+        (
+            GameProvider::Slot(SlotProvider::WM),
+            Some(r#"{"result":"http://localhost/slot"}"#),
+            url,
+        ),
+        (GameProvider::Slot(SlotProvider::Jili), None, url),
+        (GameProvider::Slot(SlotProvider::Habanero), result, None),
+    ];
+
+    for (provider, details, replay) in expected {
+        let stored = get_maria_db_provider_details(maria_db, provider).await;
+        assert!(!stored.is_empty(), "No archived bets of '{provider}'");
+
+        for (stored_details, stored_replay) in stored {
+            assert_eq!(
+                stored_details.as_deref(),
+                details,
+                "Details of '{provider}'"
+            );
+            assert_eq!(stored_replay.as_deref(), replay, "Replay of '{provider}'");
+        }
+    }
+
+    // Redirect is resolved while archiving, so the stored link holds no operator session
+    let stored =
+        get_maria_db_provider_details(maria_db, GameProvider::Slot(SlotProvider::PG)).await;
+    assert!(!stored.is_empty());
+
+    for (details, replay) in stored {
+        let replay = replay.expect("PG Soft replay is not stored");
+
+        assert_eq!(details, None);
+        assert!(replay.ends_with("/history/round"));
+    }
+
+    let left: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM public.bet_archive_details")
+        .fetch_one(maria_db)
+        .await
+        .unwrap();
+
+    assert_eq!(left, 0);
+}
+
+async fn get_maria_db_provider_details(
+    maria_db: &MySqlPool,
+    provider: GameProvider,
+) -> Vec<(Option<String>, Option<String>)> {
+    sqlx::query_as("SELECT details, replay FROM public.bet WHERE provider = ?")
+        .bind(provider.to_string())
+        .fetch_all(maria_db)
+        .await
+        .expect("Failed to get provider details from Maria DB")
+}
+
+async fn get_maria_db_provider_figures(maria_db: &MySqlPool, provider: GameProvider) -> (i64, i64) {
+    let result = sqlx::query(
+        "SELECT COUNT(*) AS count, CAST(COALESCE(SUM(wl), 0) AS SIGNED) AS wl FROM public.bet WHERE provider = ?",
+    )
+    .bind(provider.to_string())
+    .fetch_one(maria_db)
+    .await
+    .expect("Failed to sum provider bets in Maria DB");
+
+    (
+        result.try_get("count").expect("Failed to get count"),
+        result.try_get("wl").expect("Failed to get wl"),
+    )
+}
+
 #[derive(FromRow)]
 struct UserDebt {
     date: OffsetDateTime,
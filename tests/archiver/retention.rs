@@ -0,0 +1,145 @@
+use std::{env, fs};
+
+use lib::{
+    archiver::retention::{RetentionPolicy, StatusCutoff},
+    enums::provider::{GameProvider, LiveCasinoProvider, SlotProvider},
+};
+use time::{Duration, OffsetDateTime};
+use uuid::Uuid;
+
+fn load_policy(json: &str) -> anyhow::Result<RetentionPolicy> {
+    let path = env::temp_dir().join(format!("retention_{}.json", Uuid::new_v4()));
+    fs::write(&path, json).expect("Failed to write retention policy file");
+
+    let policy = RetentionPolicy::load(path.to_str().unwrap());
+    fs::remove_file(&path).expect("Failed to remove retention policy file");
+
+    policy
+}
+
+/// Retention days of every archived status, cutoff is not limited by the run
+fn days_by_status(policy: &RetentionPolicy, provider: GameProvider) -> Vec<(String, i64)> {
+    let today = OffsetDateTime::now_utc().date();
+    let mut result = vec![];
+
+    for StatusCutoff {
+        statuses,
+        cutoff_date,
+    } in policy.status_cutoffs(provider, today + Duration::days(365))
+    {
+        for status in statuses {
+            result.push((status, (today - cutoff_date).whole_days()));
+        }
+    }
+
+    result.sort();
+    result
+}
+
+fn days(pairs: &[(&str, i64)]) -> Vec<(String, i64)> {
+    let mut result: Vec<(String, i64)> = pairs
+        .iter()
+        .map(|(status, days)| (status.to_string(), *days))
+        .collect();
+
+    result.sort();
+    result
+}
+
+#[test]
+fn default_policy_archives_settled_bets_after_one_day() {
+    let policy = RetentionPolicy::default();
+
+    assert_eq!(
+        days_by_status(&policy, SlotProvider::PG.into_game_provider()),
+        days(&[
+            ("CANCELLED", 1),
+            ("CLOSED", 1),
+            ("SUSPENDED", 1),
+            ("VOID", 1)
+        ])
+    );
+}
+
+#[test]
+fn cutoff_never_goes_past_requested_date() {
+    let max_cutoff_date = OffsetDateTime::now_utc().date() - Duration::days(10);
+
+    let cutoffs = RetentionPolicy::default()
+        .status_cutoffs(SlotProvider::PG.into_game_provider(), max_cutoff_date);
+
+    assert_eq!(cutoffs.len(), 1);
+    assert_eq!(cutoffs[0].cutoff_date, max_cutoff_date);
+}
+
+#[test]
+fn excluded_statuses_replace_defaults() {
+    let policy = load_policy(r#"{ "excluded_statuses": ["ACTIVE", "SUSPENDED"] }"#).unwrap();
+
+    assert_eq!(
+        days_by_status(&policy, SlotProvider::PG.into_game_provider()),
+        days(&[("CANCELLED", 1), ("CLOSED", 1), ("PENDING", 1), ("VOID", 1)])
+    );
+}
+
+#[test]
+fn most_specific_rule_wins() {
+    let policy = load_policy(
+        r#"{
+            "default_days": 2,
+            "products": { "SLOT": 5 },
+            "providers": { "pg_slot": 10 },
+            "statuses": { "VOID": 30 }
+        }"#,
+    )
+    .unwrap();
+
+    // Status over provider
+    assert_eq!(
+        days_by_status(&policy, SlotProvider::PG.into_game_provider()),
+        days(&[
+            ("CANCELLED", 10),
+            ("CLOSED", 10),
+            ("SUSPENDED", 10),
+            ("VOID", 30)
+        ])
+    );
+
+    // Status over product
+    assert_eq!(
+        days_by_status(&policy, SlotProvider::Ameba.into_game_provider()),
+        days(&[
+            ("CANCELLED", 5),
+            ("CLOSED", 5),
+            ("SUSPENDED", 5),
+            ("VOID", 30)
+        ])
+    );
+
+    // Status over default
+    assert_eq!(
+        days_by_status(&policy, LiveCasinoProvider::Sexy.into_game_provider()),
+        days(&[
+            ("CANCELLED", 2),
+            ("CLOSED", 2),
+            ("SUSPENDED", 2),
+            ("VOID", 30)
+        ])
+    );
+}
+
+#[test]
+fn invalid_policies_are_rejected() {
+    for json in [
+        r#"{ "statuses": { "WON": 3 } }"#,
+        r#"{ "excluded_statuses": ["WON"] }"#,
+        r#"{ "products": { "CASINO": 3 } }"#,
+        r#"{ "providers": { "unknown_slot": 3 } }"#,
+        r#"{ "default_days": 0 }"#,
+        r#"{ "statuses": { "VOID": 0 } }"#,
+        r#"{ "default": 3 }"#,
+        "not json",
+    ] {
+        assert!(load_policy(json).is_err(), "{json} must be rejected");
+    }
+}
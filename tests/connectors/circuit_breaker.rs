@@ -0,0 +1,80 @@
+use claims::{assert_err, assert_ok};
+use lib::connectors::circuit_breaker::{CircuitBreaker, CircuitBreakerConfig, Transition};
+
+fn breaker(cool_down_secs: u64) -> CircuitBreaker {
+    CircuitBreaker::new(
+        "test".to_string(),
+        CircuitBreakerConfig {
+            failure_threshold: 2,
+            cool_down_secs,
+        },
+    )
+}
+
+fn call(breaker: &CircuitBreaker, succeeded: bool) -> Option<Transition> {
+    assert_ok!(breaker.acquire()).record(succeeded)
+}
+
+#[test]
+fn consecutive_failures_open_the_circuit() {
+    let breaker = breaker(60);
+
+    assert_eq!(call(&breaker, false), None);
+    // A success in between starts the count again
+    assert_eq!(call(&breaker, true), None);
+    assert_eq!(call(&breaker, false), None);
+    assert_eq!(call(&breaker, false), Some(Transition::Opened));
+
+    assert_err!(breaker.acquire());
+}
+
+#[test]
+fn successful_probe_closes_the_circuit() {
+    let breaker = breaker(0);
+
+    call(&breaker, false);
+    call(&breaker, false);
+
+    let probe = assert_ok!(breaker.acquire());
+    assert_eq!(probe.transition(), Some(Transition::HalfOpened));
+
+    // Only the probe is let through while half-open
+    assert_err!(breaker.acquire());
+
+    assert_eq!(probe.record(true), Some(Transition::Closed));
+    assert_eq!(assert_ok!(breaker.acquire()).transition(), None);
+}
+
+#[test]
+fn failed_probe_opens_the_circuit_again() {
+    let breaker = breaker(0);
+
+    call(&breaker, false);
+    call(&breaker, false);
+
+    let probe = assert_ok!(breaker.acquire());
+    assert_eq!(probe.transition(), Some(Transition::HalfOpened));
+    assert_eq!(probe.record(false), Some(Transition::Opened));
+
+    assert_eq!(
+        assert_ok!(breaker.acquire()).transition(),
+        Some(Transition::HalfOpened)
+    );
+}
+
+#[test]
+fn dropped_probe_counts_as_failure() {
+    let breaker = breaker(0);
+
+    call(&breaker, false);
+    call(&breaker, false);
+
+    let probe = assert_ok!(breaker.acquire());
+    assert_eq!(probe.transition(), Some(Transition::HalfOpened));
+    drop(probe);
+
+    // Circuit is open again instead of waiting for a probe which never finishes
+    let probe = assert_ok!(breaker.acquire());
+    assert_eq!(probe.transition(), Some(Transition::HalfOpened));
+    assert_eq!(probe.record(true), Some(Transition::Closed));
+}
@@ -0,0 +1,76 @@
+use claims::{assert_err, assert_ok};
+use lib::connectors::http::{HttpClient, HttpConfig};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn client(max_retries: u32) -> HttpClient {
+    HttpClient::new(HttpConfig {
+        max_retries,
+        retry_base_delay_ms: 1,
+        ..HttpConfig::default()
+    })
+    .unwrap()
+}
+
+#[tokio::test]
+async fn server_errors_are_retried_until_success() {
+    let server = MockServer::start().await;
+
+    // Mounted first, so it answers until its two responses are used up
+    Mock::given(method("GET"))
+        .and(path("/history"))
+        .respond_with(ResponseTemplate::new(500))
+        .up_to_n_times(2)
+        .expect(2)
+        .mount(&server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/history"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("ok"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let url = format!("{}/history", server.uri());
+    let response = client(2).send(|client| Ok(client.get(&url))).await;
+
+    let response = assert_ok!(response);
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.text().await.unwrap(), "ok");
+}
+
+#[tokio::test]
+async fn server_errors_fail_after_max_retries() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/history"))
+        .respond_with(ResponseTemplate::new(503))
+        .expect(3)
+        .mount(&server)
+        .await;
+
+    let url = format!("{}/history", server.uri());
+    let result = client(2).send(|client| Ok(client.get(&url))).await;
+
+    let error = assert_err!(result);
+    assert!(format!("{:#}", error).contains("after 3 attempts"));
+}
+
+#[tokio::test]
+async fn client_errors_are_not_retried() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/history"))
+        .respond_with(ResponseTemplate::new(404))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let url = format!("{}/history", server.uri());
+    let response = client(2).send(|client| Ok(client.get(&url))).await;
+
+    assert_eq!(assert_ok!(response).status(), 404);
+}
@@ -0,0 +1,14 @@
+use lib::helpers::crypto::jili_key;
+use time::macros::datetime;
+
+const QUERY: &str = "WagersId=123&AgentId=agent";
+
+#[test]
+fn jili_key_is_signed_with_gmt_minus_4_date() {
+    // Still June 4th in GMT-4
+    let key = jili_key(QUERY, datetime!(2024-06-05 02:30 UTC), "agent", "key").unwrap();
+    assert_eq!(key, "00000070756a700093fd802f6b730481530033000000");
+
+    let key = jili_key(QUERY, datetime!(2024-06-05 04:30 UTC), "agent", "key").unwrap();
+    assert_eq!(key, "0000003280a3e423336b6bc8b9d8bc9676e88a000000");
+}
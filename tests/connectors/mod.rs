@@ -0,0 +1,6 @@
+mod circuit_breaker;
+mod http;
+mod jili;
+mod pg_soft;
+mod rate_limit;
+mod wm;
@@ -0,0 +1,105 @@
+use lib::archiver::bets::loader::BetDetailsRequest;
+use lib::connectors::{
+    http::HttpConfig,
+    pg_soft::{Connector, PgSoftConfig},
+    BetDetailsConnector,
+};
+use lib::types::{BetID, Currency, ProviderBetID, ProviderGameVendorID, Url, UserID, Username};
+use serde_json::json;
+use uuid::Uuid;
+use wiremock::matchers::{method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+async fn mount_login_proxy(server: &MockServer, expected_calls: u64) {
+    Mock::given(method("POST"))
+        .and(path("/external/v1/Login/LoginProxy"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "data": {
+                "operator_session": "session"
+            },
+            "error": null
+        })))
+        .expect(expected_calls)
+        .named("pg_soft_login")
+        .mount(server)
+        .await;
+}
+
+async fn mount_round_history(server: &MockServer, expected_calls: u64) {
+    Mock::given(method("GET"))
+        .and(path("/history/redirect.html"))
+        .and(query_param("psid", "1001"))
+        .and(query_param("sid", "1001"))
+        .and(query_param("gid", "65"))
+        .and(query_param("type", "operator"))
+        .and(query_param("t", "session"))
+        .respond_with(ResponseTemplate::new(302).insert_header("Location", "/history/round"))
+        .expect(expected_calls)
+        .named("pg_soft_redirect")
+        .mount(server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/history/round"))
+        .respond_with(ResponseTemplate::new(200))
+        .named("pg_soft_round")
+        .mount(server)
+        .await;
+}
+
+fn connector(server: &MockServer, session_ttl_secs: u64) -> Connector {
+    Connector::new(PgSoftConfig {
+        api_url: Url(server.uri()),
+        history_url: Url(server.uri()),
+        operator_token: "operator".to_string(),
+        secret_key: "secret".to_string(),
+        ip_list: vec![],
+        session_ttl_secs,
+        http: HttpConfig::default(),
+    })
+    .unwrap()
+}
+
+fn bet() -> BetDetailsRequest {
+    BetDetailsRequest {
+        id: BetID(Uuid::new_v4()),
+        user_id: UserID(Uuid::new_v4()),
+        username: Username("player".to_string()),
+        currency: Currency("EUR".to_string()),
+        details: None,
+        transactions: vec![],
+        provider_bet_id: ProviderBetID("1001".to_string()),
+        provider_game_vendor_id: ProviderGameVendorID("65".to_string()),
+    }
+}
+
+#[tokio::test]
+async fn stored_link_is_resolved_with_a_reused_session() {
+    let server = MockServer::start().await;
+    mount_login_proxy(&server, 1).await;
+    mount_round_history(&server, 3).await;
+
+    let connector = connector(&server, 600);
+
+    for _ in 0..3 {
+        let details = connector.fetch_details(&bet()).await.unwrap().unwrap();
+
+        assert_eq!(details.details, None);
+        assert_eq!(
+            details.replay.unwrap().0,
+            format!("{}/history/round", server.uri())
+        );
+    }
+}
+
+#[tokio::test]
+async fn expired_session_is_requested_again() {
+    let server = MockServer::start().await;
+    mount_login_proxy(&server, 2).await;
+    mount_round_history(&server, 2).await;
+
+    let connector = connector(&server, 0);
+
+    connector.fetch_details(&bet()).await.unwrap();
+    connector.fetch_details(&bet()).await.unwrap();
+}
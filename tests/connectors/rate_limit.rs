@@ -0,0 +1,38 @@
+use std::time::Duration;
+
+use lib::connectors::rate_limit::{RateLimitConfig, RateLimiter};
+use tokio::time::Instant;
+
+#[tokio::test]
+async fn burst_passes_at_once_and_next_request_waits() {
+    tokio::time::pause();
+
+    let limiter = RateLimiter::new(RateLimitConfig {
+        requests_per_second: 4.0,
+        burst: 3,
+    })
+    .unwrap();
+    let started_at = Instant::now();
+
+    for _ in 0..3 {
+        limiter.acquire().await;
+    }
+
+    assert_eq!(started_at.elapsed(), Duration::ZERO);
+
+    // Timers have millisecond resolution and round up
+    limiter.acquire().await;
+    assert_waited(started_at, Duration::from_millis(250));
+
+    limiter.acquire().await;
+    assert_waited(started_at, Duration::from_millis(500));
+}
+
+fn assert_waited(started_at: Instant, expected: Duration) {
+    let elapsed = started_at.elapsed();
+
+    assert!(
+        elapsed >= expected && elapsed <= expected + Duration::from_millis(2),
+        "waited {elapsed:?}, expected {expected:?}"
+    );
+}
@@ -0,0 +1,43 @@
+use lib::connectors::{
+    http::HttpConfig,
+    wm::{Connector, WmConfig},
+};
+use lib::types::{ProviderBetID, ProviderGameVendorID, Url, Username};
+use rustc_hash::FxHashMap;
+use wiremock::matchers::any;
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[tokio::test]
+async fn unknown_game_is_skipped_without_a_request() {
+    let server = MockServer::start().await;
+
+    Mock::given(any())
+        .respond_with(ResponseTemplate::new(500))
+        .expect(0)
+        .named("wm")
+        .mount(&server)
+        .await;
+
+    let connector = Connector::new(
+        WmConfig {
+            api_url: Url(server.uri()),
+            vendor_id: "vendor".to_string(),
+            signature: "signature".to_string(),
+            ip_list: vec![],
+            http: HttpConfig::default(),
+        },
+        FxHashMap::default(),
+    )
+    .unwrap();
+
+    let history = connector
+        .get_round_history(
+            &Username("player".to_string()),
+            &ProviderBetID("1001".to_string()),
+            &ProviderGameVendorID("unknown_game".to_string()),
+        )
+        .await
+        .unwrap();
+
+    assert!(history.is_none());
+}
@@ -0,0 +1,9 @@
+use sqlx::{Executor, PgPool};
+
+pub async fn create_archive_run_summary_tables(pg: &PgPool) {
+    let sql = include_str!("../../../../../migrations/20240602120000_archive_run_summary.sql");
+
+    pg.execute(sql)
+        .await
+        .expect("Failed to create PG 'archive_run_summary' tables");
+}
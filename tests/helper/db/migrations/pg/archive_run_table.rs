@@ -0,0 +1,9 @@
+use sqlx::{Executor, PgPool};
+
+pub async fn create_archive_run_table(pg: &PgPool) {
+    let sql = include_str!("../../../../../migrations/20240601120000_archive_run.sql");
+
+    pg.execute(sql)
+        .await
+        .expect("Failed to create PG 'archive_run' table");
+}
@@ -1,15 +1,20 @@
 use sqlx::PgPool;
 
 use self::{
-    balance_table::create_balance_table, bet_tables::create_provider_bet_tables,
-    lottery_bet_table::create_lottery_bet_table, user_table::create_user_table,
+    archive_run_summary_table::create_archive_run_summary_tables,
+    archive_run_table::create_archive_run_table, balance_table::create_balance_table,
+    bet_tables::create_provider_bet_tables, lottery_bet_table::create_lottery_bet_table,
+    pending_bet_details_table::create_pending_bet_details_table, user_table::create_user_table,
 };
 
+mod archive_run_summary_table;
+mod archive_run_table;
 mod balance_table;
 mod bet_status_table;
 mod bet_tables;
 mod currency_table;
 mod lottery_bet_table;
+mod pending_bet_details_table;
 mod position_table;
 pub mod provider;
 mod user_table;
@@ -23,6 +28,11 @@ pub struct MockUrls {
     pub king_maker_mock_url: String,
     pub pragamtic_mock_url: String,
     pub royal_slot_gaming_mock_url: String,
+    pub sa_mock_url: String,
+    pub wm_mock_url: String,
+    pub pg_soft_mock_url: String,
+    pub jili_mock_url: String,
+    pub habanero_mock_url: String,
 }
 
 pub async fn create_pg_tables_and_seed(pg: &PgPool, mock_urls: MockUrls) {
@@ -35,6 +45,9 @@ pub async fn create_pg_tables_and_seed(pg: &PgPool, mock_urls: MockUrls) {
     create_provider_bet_tables(pg).await;
     create_lottery_bet_table(pg).await;
     provider::create_tables_and_seed(pg, mock_urls).await;
+    create_archive_run_table(pg).await;
+    create_archive_run_summary_tables(pg).await;
+    create_pending_bet_details_table(pg).await;
 }
 
 async fn create_index(pg: &PgPool, column: &str, table_name: &str) {
@@ -0,0 +1,10 @@
+use sqlx::{Executor, PgPool};
+
+pub async fn create_pending_bet_details_table(pg: &PgPool) {
+    let sql = include_str!("../../../../../migrations/20240603120000_pending_bet_details.sql");
+
+    // Migration has several statements, so it has to go through simple query protocol
+    pg.execute(sql)
+        .await
+        .expect("Failed to create PG 'pending_bet_details' table");
+}
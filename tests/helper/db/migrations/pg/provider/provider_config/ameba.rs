@@ -1,5 +1,5 @@
 use lib::{
-    connectors::ameba,
+    connectors::{ameba, http::HttpConfig},
     enums::provider::{GameProvider, SlotProvider},
     types::Url,
 };
@@ -10,6 +10,7 @@ pub fn get_provider_config(mock_url: String) -> (String, GameProvider) {
         ip_list: vec![],
         api_url: Url(mock_url),
         site_id: 1111,
+        http: HttpConfig::default(),
     };
 
     (
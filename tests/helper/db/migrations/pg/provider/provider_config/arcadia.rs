@@ -1,5 +1,5 @@
 use lib::{
-    connectors::arcadia,
+    connectors::{arcadia, http::HttpConfig},
     enums::provider::{GameProvider, OnlineCasinoProvider},
     types::Url,
 };
@@ -9,6 +9,7 @@ pub fn get_provider_config(mock_url: String) -> (String, GameProvider) {
         authentication: "secret".to_string(),
         ip_list: vec![],
         api_url: Url(mock_url),
+        http: HttpConfig::default(),
     };
 
     (
@@ -1,4 +1,7 @@
-use lib::{connectors, types::Url};
+use lib::{
+    connectors::{self, http::HttpConfig},
+    types::Url,
+};
 
 pub fn get_provider_config(mock_url: String) -> String {
     let config = connectors::dot_connections::DotConnectionsConfig {
@@ -7,6 +10,7 @@ pub fn get_provider_config(mock_url: String) -> String {
         ip_list: vec![],
         brand_id: "agent_id".to_string(),
         bet_data_url: Url("data_url".to_string()),
+        http: HttpConfig::default(),
     };
 
     serde_json::to_string(&config).unwrap()
@@ -0,0 +1,17 @@
+use lib::{
+    connectors::{habanero, http::HttpConfig},
+    types::Url,
+};
+use uuid::Uuid;
+
+pub fn get_provider_config(mock_url: String) -> String {
+    let config = habanero::HabaneroConfig {
+        api_url: Url(mock_url),
+        brand_id: Uuid::new_v4(),
+        api_key: Uuid::new_v4(),
+        ip_list: vec![],
+        http: HttpConfig::default(),
+    };
+
+    serde_json::to_string(&config).expect("Failed to stringify habanero config")
+}
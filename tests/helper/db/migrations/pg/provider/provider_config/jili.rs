@@ -0,0 +1,16 @@
+use lib::{
+    connectors::{http::HttpConfig, jili},
+    types::Url,
+};
+
+pub fn get_provider_config(mock_url: String) -> String {
+    let config = jili::JiliConfig {
+        api_url: Url(mock_url),
+        agent_id: "agent".to_string(),
+        agent_key: "key".to_string(),
+        ip_list: vec![],
+        http: HttpConfig::default(),
+    };
+
+    serde_json::to_string(&config).expect("Failed to stringify jili config")
+}
@@ -1,5 +1,5 @@
 use lib::{
-    connectors::king_maker,
+    connectors::{http::HttpConfig, king_maker},
     enums::provider::{GameProvider, OnlineCasinoProvider},
     types::Url,
 };
@@ -12,6 +12,7 @@ pub fn get_provider_config(mock_url: String) -> (String, GameProvider) {
         ip_list: vec![],
         api_url: Url(mock_url.clone()),
         lobby_url: Url(mock_url),
+        http: HttpConfig::default(),
     };
 
     (
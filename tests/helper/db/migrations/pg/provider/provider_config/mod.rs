@@ -1,4 +1,4 @@
-use lib::enums::provider::{LiveCasinoProvider, SlotProvider};
+use lib::enums::provider::{GameProvider, LiveCasinoProvider, OnlineCasinoProvider, SlotProvider};
 use sqlx::{Execute, PgPool, Postgres, QueryBuilder};
 
 use crate::helper::db::migrations::pg::MockUrls;
@@ -6,10 +6,15 @@ use crate::helper::db::migrations::pg::MockUrls;
 mod ameba;
 mod arcadia;
 mod dot_connections;
+mod habanero;
+mod jili;
 mod king_maker;
+mod pg_soft;
 mod pragamtic;
 mod royal_slot_gaming;
+mod sa;
 mod sexy;
+mod wm;
 
 pub async fn create_table_and_seed(pg: &PgPool, mock_urls: MockUrls) {
     let sql = include_str!("../../../../../../../migrations/20240413082655_provider_config.sql");
@@ -34,6 +39,7 @@ async fn seed(pg: &PgPool, mock_urls: MockUrls) {
     provider_configs.push(royal_slot_gaming::get_provider_config(
         mock_urls.royal_slot_gaming_mock_url,
     ));
+    provider_configs.push(sa::get_provider_config(mock_urls.sa_mock_url));
 
     let dot_connections_config_str =
         dot_connections::get_provider_config(mock_urls.dot_connections_mock_url);
@@ -46,14 +52,50 @@ async fn seed(pg: &PgPool, mock_urls: MockUrls) {
         provider_configs.push((dot_connections_config_str.clone(), dot_connections_provider));
     }
 
-    let pragmatic_config = pragamtic::get_provider_config(mock_urls.pragamtic_mock_url);
+    push_shared_config(
+        &mut provider_configs,
+        pragamtic::get_provider_config(mock_urls.pragamtic_mock_url),
+        [
+            LiveCasinoProvider::Pragmatic.into_game_provider(),
+            SlotProvider::Pragmatic.into_game_provider(),
+        ],
+    );
 
-    for provider in [
-        LiveCasinoProvider::Pragmatic.into_game_provider(),
-        SlotProvider::Pragmatic.into_game_provider(),
-    ] {
-        provider_configs.push((pragmatic_config.clone(), provider));
-    }
+    push_shared_config(
+        &mut provider_configs,
+        wm::get_provider_config(mock_urls.wm_mock_url),
+        [
+            LiveCasinoProvider::WM.into_game_provider(),
+            SlotProvider::WM.into_game_provider(),
+        ],
+    );
+
+    push_shared_config(
+        &mut provider_configs,
+        pg_soft::get_provider_config(mock_urls.pg_soft_mock_url),
+        [
+            SlotProvider::PG.into_game_provider(),
+            OnlineCasinoProvider::PG.into_game_provider(),
+        ],
+    );
+
+    push_shared_config(
+        &mut provider_configs,
+        jili::get_provider_config(mock_urls.jili_mock_url),
+        [
+            SlotProvider::Jili.into_game_provider(),
+            OnlineCasinoProvider::Jili.into_game_provider(),
+        ],
+    );
+
+    push_shared_config(
+        &mut provider_configs,
+        habanero::get_provider_config(mock_urls.habanero_mock_url),
+        [
+            SlotProvider::Habanero.into_game_provider(),
+            OnlineCasinoProvider::Habanero.into_game_provider(),
+        ],
+    );
 
     let mut query_builder: QueryBuilder<Postgres> =
         QueryBuilder::new("INSERT INTO public.provider_config (game_provider, config)");
@@ -69,3 +111,14 @@ async fn seed(pg: &PgPool, mock_urls: MockUrls) {
         .await
         .expect("Failed to insert providers' configs");
 }
+
+/// One config row per product of a provider which serves several products with the same API
+fn push_shared_config(
+    provider_configs: &mut Vec<(String, GameProvider)>,
+    config: String,
+    providers: [GameProvider; 2],
+) {
+    for provider in providers {
+        provider_configs.push((config.clone(), provider));
+    }
+}
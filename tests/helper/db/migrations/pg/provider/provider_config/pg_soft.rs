@@ -0,0 +1,18 @@
+use lib::{
+    connectors::{http::HttpConfig, pg_soft},
+    types::Url,
+};
+
+pub fn get_provider_config(mock_url: String) -> String {
+    let config = pg_soft::PgSoftConfig {
+        api_url: Url(mock_url.clone()),
+        history_url: Url(mock_url),
+        operator_token: "operator".to_string(),
+        secret_key: "secret".to_string(),
+        ip_list: vec![],
+        session_ttl_secs: 600,
+        http: HttpConfig::default(),
+    };
+
+    serde_json::to_string(&config).expect("Failed to stringify pg_soft config")
+}
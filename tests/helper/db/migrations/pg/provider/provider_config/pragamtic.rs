@@ -1,4 +1,7 @@
-use lib::{connectors::pragmatic::PragmaticConfig, types::Url};
+use lib::{
+    connectors::{http::HttpConfig, pragmatic::PragmaticConfig},
+    types::Url,
+};
 
 pub fn get_provider_config(mock_url: String) -> String {
     let config = PragmaticConfig {
@@ -10,6 +13,7 @@ pub fn get_provider_config(mock_url: String) -> String {
         provider_id: "123".to_string(),
         secure_login: "login".to_string(),
         game_server_domain: Url("local".to_string()),
+        http: HttpConfig::default(),
     };
 
     serde_json::to_string(&config).expect("Failed to stringify pragmatic config")
@@ -1,5 +1,5 @@
 use lib::{
-    connectors::royal_slot_gaming,
+    connectors::{http::HttpConfig, royal_slot_gaming},
     enums::provider::{GameProvider, SlotProvider},
     types::Url,
 };
@@ -14,6 +14,7 @@ pub fn get_provider_config(mock_url: String) -> (String, GameProvider) {
         client_secret: "V98KXA46".to_string(),
         ip_list: vec![],
         api_url: Url(mock_url),
+        http: HttpConfig::default(),
     };
 
     (
@@ -0,0 +1,21 @@
+use lib::{
+    connectors::{http::HttpConfig, sa},
+    enums::provider::{GameProvider, LiveCasinoProvider},
+    types::Url,
+};
+
+pub fn get_provider_config(mock_url: String) -> (String, GameProvider) {
+    let config = sa::SaConfig {
+        api_url: Url(mock_url),
+        secret_key: "secret".to_string(),
+        md5_key: "GgaIMaiNNtg".to_string(),
+        encrypt_key: "g9G16nTs".to_string(),
+        ip_list: vec![],
+        http: HttpConfig::default(),
+    };
+
+    (
+        serde_json::to_string(&config).expect("Failed to stringify sa config"),
+        LiveCasinoProvider::SA.into_game_provider(),
+    )
+}
@@ -1,5 +1,5 @@
 use lib::{
-    connectors,
+    connectors::{self, http::HttpConfig},
     enums::provider::{GameProvider, LiveCasinoProvider},
     types::Url,
 };
@@ -11,6 +11,7 @@ pub fn get_provider_config(mock_url: String) -> (String, GameProvider) {
         ip_list: vec![],
         agent_id: "agent_id".to_string(),
         secret_key: "".to_string(),
+        http: HttpConfig::default(),
     };
 
     (
@@ -0,0 +1,16 @@
+use lib::{
+    connectors::{http::HttpConfig, wm},
+    types::Url,
+};
+
+pub fn get_provider_config(mock_url: String) -> String {
+    let config = wm::WmConfig {
+        api_url: Url(mock_url),
+        vendor_id: "vendor".to_string(),
+        signature: "secret".to_string(),
+        ip_list: vec![],
+        http: HttpConfig::default(),
+    };
+
+    serde_json::to_string(&config).expect("Failed to stringify wm config")
+}
@@ -33,7 +33,7 @@ async fn seed(pg: &PgPool) {
                 game_id,
                 config
             ) VALUES (
-                (SELECT id FROM public.provider_game LIMIT 1),
+                (SELECT id FROM public.provider_game WHERE provider = 'royal_slot_gaming'),
                 $1
             )
         "#,
@@ -1,5 +1,5 @@
-use lib::enums::provider::{ProviderGameKind, SlotProvider};
-use sqlx::PgPool;
+use lib::enums::provider::{LiveCasinoProvider, Product, ProviderGameKind, SlotProvider};
+use sqlx::{PgPool, Postgres, QueryBuilder};
 
 use crate::helper::db::migrations::pg::create_index;
 
@@ -22,7 +22,25 @@ pub async fn create_table_and_seed(pg: &PgPool) {
 }
 
 async fn seed(pg: &PgPool) {
-    sqlx::query(&format!(
+    let games = [
+        (
+            Product::Slot,
+            SlotProvider::RoyalSlotGaming.into_game_provider(),
+            ProviderGameKind::Baccarat,
+        ),
+        (
+            Product::LiveCasino,
+            LiveCasinoProvider::WM.into_game_provider(),
+            ProviderGameKind::Baccarat,
+        ),
+        (
+            Product::Slot,
+            SlotProvider::WM.into_game_provider(),
+            ProviderGameKind::Slot,
+        ),
+    ];
+
+    let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new(
         r#"
             INSERT INTO public.provider_game (
                 vendor_id,
@@ -31,19 +49,22 @@ async fn seed(pg: &PgPool) {
                 label,
                 kind,
                 visible
-            ) VALUES (
-                '{PROVIDER_VENDOR_ID}',
-                'SLOT',
-                '{}',
-                '{PROVIDER_GAME_LABEL}',
-                '{}',
-                TRUE
             )
         "#,
-        SlotProvider::RoyalSlotGaming.to_string(),
-        ProviderGameKind::Baccarat.to_string()
-    ))
-    .execute(pg)
-    .await
-    .expect("Failed to seed provider_game");
+    );
+
+    query_builder.push_values(games, |mut b, (product, provider, kind)| {
+        b.push_bind(PROVIDER_VENDOR_ID)
+            .push_bind(product.to_string())
+            .push_bind(provider.to_string())
+            .push_bind(PROVIDER_GAME_LABEL)
+            .push_bind(kind.to_string())
+            .push_bind(true);
+    });
+
+    query_builder
+        .build()
+        .execute(pg)
+        .await
+        .expect("Failed to seed provider_game");
 }
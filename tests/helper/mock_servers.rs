@@ -1,6 +1,6 @@
 use serde_json::json;
-use wiremock::matchers::{method, path, path_regex};
-use wiremock::{Mock, ResponseTemplate};
+use wiremock::matchers::{method, path, path_regex, query_param};
+use wiremock::{Mock, MockBuilder, MockServer, ResponseTemplate};
 
 use super::test_data::TestData;
 
@@ -88,4 +88,118 @@ pub async fn mount_mock_servers(t_data: &TestData) {
         .named("royal_slot")
         .mount(&t_data.mock_servers.royal_slot_gaming_mock_server)
         .await;
+
+    mount_details_mock(
+        &t_data.mock_servers.sa_mock_server,
+        "sa",
+        Mock::given(method("POST")).and(path("/api/api.aspx")),
+        ResponseTemplate::new(200).set_body_string(
+            "<GetBetDetailURLResponse><ErrorMsgId>0</ErrorMsgId><ErrorMsg>Success</ErrorMsg><URL>http://localhost</URL></GetBetDetailURLResponse>",
+        ),
+    )
+    .await;
+
+    mount_details_mock(
+        &t_data.mock_servers.wm_mock_server,
+        "wm_baccarat",
+        Mock::given(method("GET")).and(path("/history/baccarat")),
+        ResponseTemplate::new(200).set_body_json(json!({
+            "errorCode": 0,
+            "errorMessage": "Success",
+            "result": {
+                "resultUrl": "http://localhost/baccarat",
+                "replayUrl": "http://localhost"
+            }
+        })),
+    )
+    .await;
+
+    mount_details_mock(
+        &t_data.mock_servers.wm_mock_server,
+        "wm_slot",
+        Mock::given(method("GET")).and(path("/history/slot")),
+        ResponseTemplate::new(200).set_body_json(json!({
+            "errorCode": 0,
+            "errorMessage": "Success",
+            "result": {
+                "resultUrl": "http://localhost/slot",
+                "replayUrl": "http://localhost"
+            }
+        })),
+    )
+    .await;
+
+    let pg_soft_mock_server = &t_data.mock_servers.pg_soft_mock_server;
+
+    mount_details_mock(
+        pg_soft_mock_server,
+        "pg_soft_login",
+        Mock::given(method("POST")).and(path("/external/v1/Login/LoginProxy")),
+        ResponseTemplate::new(200).set_body_json(json!({
+            "data": {
+                "operator_session": "session"
+            },
+            "error": null
+        })),
+    )
+    .await;
+
+    // Stored replay is the page PG Soft redirects to, not the redirect with the session
+    mount_details_mock(
+        pg_soft_mock_server,
+        "pg_soft_redirect",
+        Mock::given(method("GET"))
+            .and(path("/history/redirect.html"))
+            .and(query_param("t", "session")),
+        ResponseTemplate::new(302).insert_header("Location", "/history/round"),
+    )
+    .await;
+
+    Mock::given(method("GET"))
+        .and(path("/history/round"))
+        .respond_with(ResponseTemplate::new(200))
+        .named("pg_soft_round")
+        .mount(pg_soft_mock_server)
+        .await;
+
+    mount_details_mock(
+        &t_data.mock_servers.jili_mock_server,
+        "jili",
+        Mock::given(method("POST")).and(path("/GetGameDetailUrl")),
+        ResponseTemplate::new(200).set_body_json(json!({
+            "ErrorCode": 0,
+            "Message": "",
+            "Data": {
+                "Url": "http://localhost"
+            }
+        })),
+    )
+    .await;
+
+    mount_details_mock(
+        &t_data.mock_servers.habanero_mock_server,
+        "habanero",
+        Mock::given(method("POST")).and(path("/jsonapi/GetGameDetails")),
+        ResponseTemplate::new(200).set_body_json(json!({
+            "Success": true,
+            "Message": "",
+            "GameDetailsUrl": "http://localhost"
+        })),
+    )
+    .await;
+}
+
+/// Every archived provider with a connector must be asked for details at least once
+async fn mount_details_mock(
+    server: &MockServer,
+    name: &str,
+    request: MockBuilder,
+    response: ResponseTemplate,
+) {
+    request
+        .respond_with(response)
+        .expect(1..)
+        .named(name)
+        .mount(server)
+        .await;
 }
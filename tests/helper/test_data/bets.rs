@@ -77,6 +77,10 @@ pub async fn create_bets(
                         provider_game_vendor_label: ProviderGameVendorLabel(
                             PROVIDER_GAME_LABEL.to_string(),
                         ),
+                        kind: match provider {
+                            GameProvider::Lottery(kind) => Some(kind),
+                            _ => None,
+                        },
                     });
             }
 
@@ -21,10 +21,7 @@ use super::{
     archive_tables::{create_credit_debt_table, create_opening_balance_table},
     db::{
         create_archive_schema, drop_schema,
-        migrations::{
-            maria_db,
-            pg::{self, MockUrls},
-        },
+        migrations::{maria_db, pg::MockUrls},
     },
     user::{save_balance, Balance, User},
 };
@@ -48,6 +45,11 @@ pub struct MockServers {
     pub king_maker_mock_server: MockServer,
     pub pragamtic_mock_server: MockServer,
     pub royal_slot_gaming_mock_server: MockServer,
+    pub sa_mock_server: MockServer,
+    pub wm_mock_server: MockServer,
+    pub pg_soft_mock_server: MockServer,
+    pub jili_mock_server: MockServer,
+    pub habanero_mock_server: MockServer,
 }
 
 impl MockServers {
@@ -60,6 +62,11 @@ impl MockServers {
             royal_slot_gaming_mock_server: MockServer::start().await,
             ameba_mock_server: MockServer::start().await,
             arcadia_mock_server: MockServer::start().await,
+            sa_mock_server: MockServer::start().await,
+            wm_mock_server: MockServer::start().await,
+            pg_soft_mock_server: MockServer::start().await,
+            jili_mock_server: MockServer::start().await,
+            habanero_mock_server: MockServer::start().await,
         }
     }
 
@@ -72,6 +79,11 @@ impl MockServers {
             king_maker_mock_url: self.king_maker_mock_server.uri(),
             pragamtic_mock_url: self.pragamtic_mock_server.uri(),
             royal_slot_gaming_mock_url: self.royal_slot_gaming_mock_server.uri(),
+            sa_mock_url: self.sa_mock_server.uri(),
+            wm_mock_url: self.wm_mock_server.uri(),
+            pg_soft_mock_url: self.pg_soft_mock_server.uri(),
+            jili_mock_url: self.jili_mock_server.uri(),
+            habanero_mock_url: self.habanero_mock_server.uri(),
         }
     }
 }
@@ -116,15 +128,23 @@ pub async fn prepare_data(
     }
 }
 
-pub const TEST_PROVIDERS: [GameProvider; 9] = [
+pub const TEST_PROVIDERS: [GameProvider; 16] = [
     GameProvider::LiveCasino(LiveCasinoProvider::Sexy),
+    GameProvider::LiveCasino(LiveCasinoProvider::SA),
     GameProvider::Slot(SlotProvider::Ameba),
     GameProvider::OnlineCasino(OnlineCasinoProvider::Arcadia),
     GameProvider::Slot(SlotProvider::Relax), // dot connections
     GameProvider::OnlineCasino(OnlineCasinoProvider::Kingmaker),
     GameProvider::Slot(SlotProvider::Pragmatic),
     GameProvider::Slot(SlotProvider::RoyalSlotGaming),
+    GameProvider::LiveCasino(LiveCasinoProvider::WM),
+    GameProvider::Slot(SlotProvider::WM),
+    GameProvider::Slot(SlotProvider::PG),
+    GameProvider::Slot(SlotProvider::Jili),
+    GameProvider::Slot(SlotProvider::Habanero),
     GameProvider::Lottery(Lottery::StockDowJones),
+    // Shares the lottery table with the one above
+    GameProvider::Lottery(Lottery::Thai),
     GameProvider::Sport(Sportsbook::SingleNonLive),
 ];
 
@@ -149,9 +169,13 @@ async fn create_initial_balance(pg_pool: &PgPool, users: &[User], start_date: Da
         })
         .collect();
 
-    insert_opening_balance_records(pg_pool, initial_opening_balance, start_date)
+    let mut pg_transaction = pg_pool.begin().await.unwrap();
+
+    insert_opening_balance_records(&mut pg_transaction, initial_opening_balance, start_date)
         .await
-        .unwrap()
+        .unwrap();
+
+    pg_transaction.commit().await.unwrap()
 }
 
 /// Creates opening balance tables from initial date to now
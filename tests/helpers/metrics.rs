@@ -0,0 +1,86 @@
+use std::{
+    fs,
+    net::{TcpListener, TcpStream},
+    path::Path,
+    time::Instant,
+};
+
+use anyhow::anyhow;
+use claims::{assert_err, assert_ok};
+use lib::enums::provider::{GameProvider, SlotProvider};
+use lib::helpers::metrics;
+use uuid::Uuid;
+
+#[tokio::test]
+async fn textfile_contains_observed_metrics() {
+    let provider = GameProvider::Slot(SlotProvider::Ameba);
+    metrics::observe_chunk(provider, Instant::now(), 3);
+
+    let result =
+        metrics::observe_connector("textfile_test", async { Err::<(), _>(anyhow!("Timeout")) });
+    assert_err!(result.await);
+
+    let path = std::env::temp_dir().join(format!("archiver_metrics_{}.prom", Uuid::new_v4()));
+    let path = path.to_str().unwrap();
+
+    assert_ok!(metrics::write_textfile(path));
+    let content = fs::read_to_string(path).unwrap();
+    fs::remove_file(path).unwrap();
+
+    assert!(!Path::new(&format!("{path}.tmp")).exists());
+    assert!(content.contains(&format!(
+        "archiver_bets_archived_total{{provider=\"{provider}\"}}"
+    )));
+    assert!(content.contains("archiver_connector_errors_total{connector=\"textfile_test\"} 1"));
+    assert!(content.contains(
+        "archiver_connector_request_duration_seconds_count{connector=\"textfile_test\"} 1"
+    ));
+}
+
+#[tokio::test]
+async fn server_answers_only_get_metrics() {
+    metrics::observe_chunk(GameProvider::Slot(SlotProvider::Ameba), Instant::now(), 1);
+
+    let addr = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .to_string();
+
+    let server_addr = addr.clone();
+    tokio::spawn(async move { metrics::serve(&server_addr).await });
+
+    let client = reqwest::Client::new();
+    let url = format!("http://{addr}/metrics");
+
+    let mut response = client.get(&url).send().await;
+
+    // The listener is bound in the background
+    for _ in 0..50 {
+        if response.is_ok() {
+            break;
+        }
+
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        response = client.get(&url).send().await;
+    }
+
+    let response = response.unwrap();
+    assert_eq!(response.status(), 200);
+
+    // A connection which never sends its request doesn't block the next one
+    let _silent = TcpStream::connect(&addr).unwrap();
+    let response = client.get(&url).send().await.unwrap();
+    assert_eq!(response.status(), 200);
+    assert!(response
+        .text()
+        .await
+        .unwrap()
+        .contains("archiver_bets_archived_total"));
+
+    let response = client.get(format!("http://{addr}/")).send().await.unwrap();
+    assert_eq!(response.status(), 404);
+
+    let response = client.post(&url).send().await.unwrap();
+    assert_eq!(response.status(), 405);
+}
@@ -1,2 +1,4 @@
 mod helper;
 mod archiver;
+mod connectors;
+mod helpers;